@@ -0,0 +1,124 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rayon::prelude::*;
+use uncpath::{convert_many, convert_to_posix, parse_unc_path, MappingTable, MountMapping};
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_unc_path");
+    for (format, input) in [
+        ("windows", r"\\server\shared\projects\2024\report.docx"),
+        ("unix", "//server/shared/projects/2024/report.docx"),
+        ("smb", "smb://server/shared/projects/2024/report.docx"),
+    ] {
+        group.bench_with_input(BenchmarkId::from_parameter(format), input, |b, input| {
+            b.iter(|| parse_unc_path(black_box(input)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+/// A table of `n` mappings, `host{i}/share{i}:/mnt/{i}`.
+fn table(n: usize) -> MappingTable {
+    MappingTable::try_from_iter((0..n).map(|i| {
+        MountMapping::new(
+            &format!("host{}", i),
+            &format!("share{}", i),
+            &format!("/mnt/{}", i),
+        )
+    }))
+    .unwrap()
+}
+
+fn convert(c: &mut Criterion) {
+    let mut group = c.benchmark_group("convert_to_posix");
+    for n in [10, 100, 1000] {
+        let table = table(n);
+        // The last mapping added, so a linear scan would be at its worst.
+        let input = format!(r"\\host{}\share{}\projects\report.docx", n - 1, n - 1);
+        group.bench_with_input(BenchmarkId::from_parameter(n), &input, |b, input| {
+            b.iter(|| convert_to_posix(black_box(input), &table).unwrap())
+        });
+    }
+    group.finish();
+}
+
+/// `n` sibling subpath mappings under one share plus a chain of nested
+/// ones, `a`, `a/b`, ... eight deep, all overlapping the input below.
+fn nested_table(n: usize) -> MappingTable {
+    let siblings = (0..n).map(|i| {
+        MountMapping::new(
+            "server",
+            &format!("share/dir{}", i),
+            &format!("/mnt/dir{}", i),
+        )
+    });
+    let chain = (1..=8).map(|depth| {
+        let subpath = ["a", "b", "c", "d", "e", "f", "g", "h"][..depth].join("/");
+        MountMapping::new(
+            "server",
+            &format!("share/{}", subpath),
+            &format!("/mnt/depth{}", depth),
+        )
+    });
+    MappingTable::try_from_iter(siblings.chain(chain)).unwrap()
+}
+
+fn convert_nested(c: &mut Criterion) {
+    let mut group = c.benchmark_group("convert_to_posix_nested");
+    let input = r"\\server\share\a\b\c\d\e\f\g\h\report.docx";
+    for n in [10, 100, 1000] {
+        let table = nested_table(n);
+        group.bench_with_input(BenchmarkId::from_parameter(n), input, |b, input| {
+            b.iter(|| convert_to_posix(black_box(input), &table).unwrap())
+        });
+    }
+    group.finish();
+}
+
+/// A chunk of `--stdin` input (4096 paths with `--jobs` above 1) spread
+/// over a table of 100 mappings, converted serially as with `--jobs 1`
+/// and on rayon pools of several sizes as with `--jobs N`, to confirm the
+/// pool pays for itself on this machine.
+fn convert_batch(c: &mut Criterion) {
+    const INPUTS: usize = 4096;
+    let table = table(100);
+    let inputs: Vec<String> = (0..INPUTS)
+        .map(|i| {
+            format!(
+                r"\\host{}\share{}\projects\{}\report.docx",
+                i % 100,
+                i % 100,
+                i
+            )
+        })
+        .collect();
+
+    let mut group = c.benchmark_group("convert_batch");
+    group.throughput(Throughput::Elements(INPUTS as u64));
+    group.bench_function("serial", |b| {
+        b.iter(|| convert_many(inputs.iter().map(String::as_str), &table))
+    });
+    let cpus = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut jobs = vec![2, 4, cpus];
+    jobs.sort_unstable();
+    jobs.dedup();
+    for jobs in jobs {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(jobs)
+            .build()
+            .unwrap();
+        group.bench_function(BenchmarkId::new("jobs", jobs), |b| {
+            b.iter(|| {
+                pool.install(|| {
+                    inputs
+                        .par_iter()
+                        .map(|input| convert_to_posix(input, &table))
+                        .collect::<Vec<_>>()
+                })
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse, convert, convert_nested, convert_batch);
+criterion_main!(benches);
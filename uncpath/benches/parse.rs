@@ -0,0 +1,30 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use uncpath::{parse_unc_path, parse_unc_path_ref};
+
+const INPUTS: [&str; 3] = [
+    r"\\server\shared\projects\2024\report.docx",
+    "//server/shared/projects/2024/report.docx",
+    "smb://server/shared/projects/2024/report.docx",
+];
+
+fn owned_vs_borrowed(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    group.bench_function("owned", |b| {
+        b.iter(|| {
+            for input in INPUTS {
+                black_box(parse_unc_path(black_box(input)).unwrap());
+            }
+        })
+    });
+    group.bench_function("borrowed", |b| {
+        b.iter(|| {
+            for input in INPUTS {
+                black_box(parse_unc_path_ref(black_box(input)).unwrap());
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, owned_vs_borrowed);
+criterion_main!(benches);
@@ -0,0 +1,249 @@
+use crate::errors::{Result, UncPathError};
+use crate::mapping::MountMapping;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Newest config schema version this build reads and the one it writes.
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Profile loaded from a config with [`profiles`](MappingConfig::profiles)
+/// when none is named.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// How deeply [`include`](MappingConfig::include)s may nest below the file
+/// that was loaded.
+pub const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// A whole mapping table as stored on disk, tagged with a schema version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MappingConfig {
+    /// Schema version; files that leave it out, as TOML files may, are
+    /// read as version 1.
+    #[serde(default = "first_version")]
+    pub version: u32,
+    /// Further mapping files, relative to this one's directory, that
+    /// [`load_from_file`](crate::MappingTable::load_from_file) loads before
+    /// this file's own mappings.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<PathBuf>,
+    /// Mappings loaded whichever profile is selected.
+    #[serde(default)]
+    pub mappings: Vec<MountMapping>,
+    /// Named sets of mappings, e.g. `dev` and `prod`, of which one is
+    /// loaded on top of [`mappings`](Self::mappings).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Vec<MountMapping>>,
+}
+
+impl MappingConfig {
+    /// Wrap `mappings` with the current schema version.
+    pub fn new(mappings: Vec<MountMapping>) -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            include: Vec::new(),
+            mappings,
+            profiles: BTreeMap::new(),
+        }
+    }
+
+    /// The shared mappings followed by those of `profile`, or of
+    /// [`DEFAULT_PROFILE`] if there is one and `profile` is `None`.
+    /// Configs without profiles ignore `profile`; otherwise naming a
+    /// profile the config lacks fails with [`UncPathError::UnknownProfile`].
+    pub fn into_mappings(mut self, profile: Option<&str>) -> Result<Vec<MountMapping>> {
+        if self.profiles.is_empty() {
+            return Ok(self.mappings);
+        }
+        let selected = match profile {
+            Some(name) => {
+                self.profiles
+                    .remove(name)
+                    .ok_or_else(|| UncPathError::UnknownProfile {
+                        name: name.to_string(),
+                        available: self.profiles.keys().cloned().collect::<Vec<_>>().join(", "),
+                    })?
+            }
+            None => self.profiles.remove(DEFAULT_PROFILE).unwrap_or_default(),
+        };
+        self.mappings.extend(selected);
+        Ok(self.mappings)
+    }
+
+    /// Fail with [`UncPathError::UnsupportedVersion`] unless this build
+    /// understands the schema version.
+    pub fn check_version(&self) -> Result<()> {
+        check_version(self.version)
+    }
+}
+
+fn first_version() -> u32 {
+    1
+}
+
+fn check_version(version: u32) -> Result<()> {
+    if (1..=CONFIG_VERSION).contains(&version) {
+        Ok(())
+    } else {
+        Err(UncPathError::UnsupportedVersion(version))
+    }
+}
+
+/// Just the version of a config, read before the rest so that a newer
+/// schema is reported as such rather than as a parse error.
+#[derive(Deserialize)]
+struct VersionProbe {
+    version: Option<u32>,
+}
+
+impl VersionProbe {
+    fn check(&self) -> Result<()> {
+        self.version.map_or(Ok(()), check_version)
+    }
+}
+
+/// On-disk formats a mapping file can use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    /// A JSON array of mappings, or a [`MappingConfig`] object.
+    Json,
+    /// [JSON5](https://json5.org): JSON plus comments, trailing commas and
+    /// unquoted keys.
+    ///
+    /// ```
+    /// use uncpath::config::ConfigFormat;
+    ///
+    /// let content = r#"{
+    ///     version: 1,
+    ///     mappings: [
+    ///         // The team share, mounted by autofs.
+    ///         { host: "fs01", share: "team", mount_point: "/mnt/team" },
+    ///     ],
+    /// }"#;
+    /// let mappings = ConfigFormat::Json5.parse(content).unwrap();
+    /// assert_eq!(mappings[0].mount_point, "/mnt/team");
+    /// assert!(ConfigFormat::Json.parse(content).is_err());
+    /// ```
+    Json5,
+    /// `[[mapping]]` tables with an optional top-level `version`.
+    Toml,
+    /// A YAML list of mappings, or a [`MappingConfig`] map.
+    Yaml,
+}
+
+impl ConfigFormat {
+    /// Guess the format from the file extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_lowercase();
+        match ext.as_str() {
+            "json" => Some(Self::Json),
+            "json5" => Some(Self::Json5),
+            "toml" => Some(Self::Toml),
+            "yaml" | "yml" => Some(Self::Yaml),
+            _ => None,
+        }
+    }
+
+    /// Parse the mappings in `content`, with the default profile if it has
+    /// profiles.
+    pub fn parse(self, content: &str) -> Result<Vec<MountMapping>> {
+        self.parse_config(content)?.into_mappings(None)
+    }
+
+    /// Parse either the bare list of mappings (treated as the current
+    /// version) or a versioned [`MappingConfig`].
+    pub fn parse_config(self, content: &str) -> Result<MappingConfig> {
+        match self {
+            Self::Json if content.trim_start().starts_with('{') => {
+                let probe: VersionProbe = serde_json::from_str(content)?;
+                probe.check()?;
+                Ok(serde_json::from_str(content)?)
+            }
+            Self::Json => Ok(MappingConfig::new(serde_json::from_str(content)?)),
+            Self::Json5 => {
+                let value: serde_json::Value = json5::from_str(content)?;
+                if !value.is_object() {
+                    return Ok(MappingConfig::new(serde_json::from_value(value)?));
+                }
+                let probe: VersionProbe = serde_json::from_value(value.clone())?;
+                probe.check()?;
+                Ok(serde_json::from_value(value)?)
+            }
+            Self::Toml => {
+                let probe: VersionProbe = toml::from_str(content)?;
+                probe.check()?;
+                let file: TomlFile = toml::from_str(content)?;
+                Ok(MappingConfig {
+                    include: file.include,
+                    profiles: file.profiles,
+                    ..MappingConfig::new(file.mapping)
+                })
+            }
+            Self::Yaml => {
+                let value: serde_yaml::Value = serde_yaml::from_str(content)?;
+                if !value.is_mapping() {
+                    return Ok(MappingConfig::new(serde_yaml::from_value(value)?));
+                }
+                let probe: VersionProbe = serde_yaml::from_value(value.clone())?;
+                probe.check()?;
+                Ok(serde_yaml::from_value(value)?)
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct TomlFile {
+    #[serde(default)]
+    include: Vec<PathBuf>,
+    #[serde(default)]
+    mapping: Vec<MountMapping>,
+    /// `[[profiles.NAME]]` tables.
+    #[serde(default)]
+    profiles: BTreeMap<String, Vec<MountMapping>>,
+}
+
+/// Parse `content` read from `path`, choosing the format by extension and
+/// otherwise trying JSON, TOML and YAML in turn. If every format fails, the
+/// JSON error is returned.
+pub fn parse_mappings(path: &Path, content: &str) -> Result<Vec<MountMapping>> {
+    parse_config(path, content)?.into_mappings(None)
+}
+
+/// Like [`parse_mappings`], keeping the schema version.
+pub fn parse_config(path: &Path, content: &str) -> Result<MappingConfig> {
+    parse_config_with(path, content, false)
+}
+
+/// Like [`parse_config`], reading `.json` files (and trying unknown
+/// extensions) as JSON5 when `allow_comments` is set, so that they may
+/// carry comments and trailing commas.
+pub fn parse_config_with(
+    path: &Path,
+    content: &str,
+    allow_comments: bool,
+) -> Result<MappingConfig> {
+    let json = if allow_comments {
+        ConfigFormat::Json5
+    } else {
+        ConfigFormat::Json
+    };
+    if let Some(format) = ConfigFormat::from_path(path) {
+        let format = if format == ConfigFormat::Json {
+            json
+        } else {
+            format
+        };
+        return format.parse_config(content);
+    }
+
+    json.parse_config(content).or_else(|json_err| {
+        if let UncPathError::UnsupportedVersion(_) = json_err {
+            return Err(json_err);
+        }
+        [ConfigFormat::Toml, ConfigFormat::Yaml]
+            .into_iter()
+            .find_map(|format| format.parse_config(content).ok())
+            .ok_or(json_err)
+    })
+}
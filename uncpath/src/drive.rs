@@ -0,0 +1,128 @@
+//! Drive-letter paths as seen from Unix layers on Windows: WSL mounts
+//! drive `C:` at `/mnt/c`, Cygwin at `/cygdrive/c` (MSYS2 at `/c`). These
+//! conversions work on drive letters only and never consult the mount
+//! table; a UNC path takes part through the drive it is mapped to (see
+//! [`MappingTable::add_drive_mapping`]).
+
+use crate::convert::{normalize_segments, split_drive_path, UncPath};
+use crate::errors::{Result, UncPathError};
+use crate::mapping::MappingTable;
+
+/// Directory WSL mounts Windows drives under.
+pub const WSL_PREFIX: &str = "/mnt";
+
+/// Cygwin's default cygdrive prefix. It can be changed in `/etc/fstab`;
+/// MSYS2 sets it to `/`.
+pub const CYGDRIVE_PREFIX: &str = "/cygdrive";
+
+/// `X:\dir` as `<prefix>/x/dir`.
+fn to_mounted(prefix: &str, letter: char, path: &str, trailing_slash: bool) -> String {
+    let mut mounted = format!(
+        "{}/{}",
+        prefix.trim_end_matches('/'),
+        letter.to_ascii_lowercase()
+    );
+    let path = normalize_segments(path);
+    if !path.is_empty() {
+        mounted.push('/');
+        mounted.push_str(&path);
+    }
+    if trailing_slash {
+        mounted.push('/');
+    }
+    mounted
+}
+
+/// `<prefix>/x/dir` as `X:\dir`, or `None` if `path` is not below
+/// `<prefix>/<letter>`.
+fn from_mounted(prefix: &str, path: &str) -> Option<String> {
+    let rest = path.strip_prefix(prefix.trim_end_matches('/'))?;
+    let rest = rest.strip_prefix('/')?;
+    let (letter, rest) = rest.split_once('/').unwrap_or((rest, ""));
+    let mut chars = letter.chars();
+    let letter = match (chars.next(), chars.next()) {
+        (Some(letter), None) if letter.is_ascii_alphabetic() => letter.to_ascii_uppercase(),
+        _ => return None,
+    };
+    Some(format!("{}:\\{}", letter, rest.replace('/', "\\")))
+}
+
+/// `C:\Users\me` as the WSL path `/mnt/c/Users/me`. `None` unless `path`
+/// is a drive-letter path.
+///
+/// ```
+/// use uncpath::drive::{from_wsl, to_wsl};
+///
+/// assert_eq!(to_wsl(r"C:\Users\me").as_deref(), Some("/mnt/c/Users/me"));
+/// assert_eq!(from_wsl("/mnt/c/Users/me").as_deref(), Some(r"C:\Users\me"));
+/// assert_eq!(from_wsl("/mnt/nas/x"), None);
+/// ```
+pub fn to_wsl(path: &str) -> Option<String> {
+    to_cygdrive(path, WSL_PREFIX)
+}
+
+/// The Windows path of a WSL `/mnt/<letter>/...` path. `None` for paths
+/// outside the drive mounts.
+pub fn from_wsl(path: &str) -> Option<String> {
+    from_cygdrive(path, WSL_PREFIX)
+}
+
+/// The WSL path of `unc`, through the drive its share is mapped to in
+/// `table`. Fails with [`UncPathError::NotOnDrive`] if there is none.
+pub fn unc_to_wsl(unc: &UncPath, table: &MappingTable) -> Result<String> {
+    unc_to_cygdrive(unc, table, WSL_PREFIX)
+}
+
+/// Convert a drive-letter path or any UNC form
+/// [`parse_unc_path`](crate::parse_unc_path) reads to its WSL path.
+pub fn convert_to_wsl(input: &str, table: &MappingTable) -> Result<String> {
+    convert_to_cygdrive(input, table, WSL_PREFIX)
+}
+
+/// `C:\Users\me` as `<prefix>/c/Users/me`, the Cygwin form for the
+/// cygdrive `prefix` (usually [`CYGDRIVE_PREFIX`]). `None` unless `path`
+/// is a drive-letter path.
+///
+/// ```
+/// use uncpath::drive::{from_cygdrive, to_cygdrive, CYGDRIVE_PREFIX};
+///
+/// assert_eq!(to_cygdrive(r"C:\src", CYGDRIVE_PREFIX).as_deref(), Some("/cygdrive/c/src"));
+/// assert_eq!(to_cygdrive(r"C:\src", "/").as_deref(), Some("/c/src"));
+/// assert_eq!(from_cygdrive("/cygdrive/d/x", CYGDRIVE_PREFIX).as_deref(), Some(r"D:\x"));
+/// ```
+pub fn to_cygdrive(path: &str, prefix: &str) -> Option<String> {
+    let path = path.trim();
+    let (letter, rest) = split_drive_path(path)?;
+    let trailing_slash = path.len() > 3 && path.ends_with(['/', '\\']);
+    Some(to_mounted(prefix, letter, &rest, trailing_slash))
+}
+
+/// The Windows path of a `<prefix>/<letter>/...` path. `None` for paths
+/// outside the drive mounts.
+pub fn from_cygdrive(path: &str, prefix: &str) -> Option<String> {
+    from_mounted(prefix, path.trim())
+}
+
+/// The path of `unc` below the cygdrive `prefix`, through the drive its
+/// share is mapped to in `table`. Fails with [`UncPathError::NotOnDrive`]
+/// if there is none.
+pub fn unc_to_cygdrive(unc: &UncPath, table: &MappingTable, prefix: &str) -> Result<String> {
+    let letter =
+        table
+            .drive_for(&unc.host, &unc.share)
+            .ok_or_else(|| UncPathError::NotOnDrive {
+                host: unc.host.clone(),
+                share: unc.share.clone(),
+            })?;
+    Ok(to_mounted(prefix, letter, &unc.path, unc.trailing_slash))
+}
+
+/// Convert a drive-letter path or any UNC form the table's
+/// [`parser`](MappingTable::parser) reads to its path below the cygdrive
+/// `prefix`.
+pub fn convert_to_cygdrive(input: &str, table: &MappingTable, prefix: &str) -> Result<String> {
+    match to_cygdrive(input, prefix) {
+        Some(path) => Ok(path),
+        None => unc_to_cygdrive(&table.parser().parse(input)?, table, prefix),
+    }
+}
@@ -0,0 +1,119 @@
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+/// Errors produced while parsing, mapping or converting UNC paths.
+#[derive(Debug, Error)]
+pub enum UncPathError {
+    #[error("invalid UNC path format: {0}")]
+    InvalidFormat(String),
+
+    #[error("'{0}' names a server but no share")]
+    MissingShare(String),
+
+    #[error("unsupported URL scheme '{scheme}://' (supported: {supported})")]
+    UnsupportedScheme { scheme: String, supported: String },
+
+    #[error("no mapping found for \\\\{host}\\{share}")]
+    MappingNotFound { host: String, share: String },
+
+    #[error("drive {0}: is not mapped to a share")]
+    DriveNotMapped(char),
+
+    #[error("\\\\{host}\\{share} is not mapped to a drive")]
+    NotOnDrive { host: String, share: String },
+
+    #[error("no mount point is a prefix of: {0}")]
+    NoMatchingMountPoint(String),
+
+    #[error("invalid mapping: {0}")]
+    InvalidMapping(String),
+
+    #[error("conflicting mappings: {0}")]
+    ConflictingMappings(String),
+
+    #[error("invalid regex in mapping {0}")]
+    InvalidRegex(String),
+
+    #[error("mount point {0} does not exist")]
+    MountUnavailable(String),
+
+    #[error("undefined variable ${name} in mount point '{mount_point}'")]
+    UndefinedVariable { name: String, mount_point: String },
+
+    #[error("no profile named '{name}' (available: {available})")]
+    UnknownProfile { name: String, available: String },
+
+    #[error("include cycle: {0}")]
+    IncludeCycle(String),
+
+    #[error("includes nest more than {max} deep at {}", .0.display(), max = crate::config::MAX_INCLUDE_DEPTH)]
+    IncludeTooDeep(PathBuf),
+
+    #[error("unsupported config version {0} (this build reads up to {max})", max = crate::config::CONFIG_VERSION)]
+    UnsupportedVersion(u32),
+
+    #[error("JSON error: {0}")]
+    JsonError(#[from] serde_json::Error),
+
+    #[error("JSON5 error: {0}")]
+    Json5Error(#[from] json5::Error),
+
+    #[error("TOML error: {0}")]
+    TomlError(#[from] toml::de::Error),
+
+    #[error("YAML error: {0}")]
+    YamlError(#[from] serde_yaml::Error),
+
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    /// A mapping file that could not be read or parsed; `source` is the
+    /// underlying IO or format error.
+    #[error("failed to load mappings from {}: {source}", path.display())]
+    ConfigError {
+        path: PathBuf,
+        source: Box<UncPathError>,
+    },
+}
+
+impl UncPathError {
+    /// Process exit status for this error, stable across releases:
+    ///
+    /// | code | errors |
+    /// |------|--------|
+    /// | 2 | [`InvalidFormat`](Self::InvalidFormat), [`MissingShare`](Self::MissingShare), [`UnsupportedScheme`](Self::UnsupportedScheme) |
+    /// | 3 | [`MappingNotFound`](Self::MappingNotFound), [`DriveNotMapped`](Self::DriveNotMapped), [`NotOnDrive`](Self::NotOnDrive), [`NoMatchingMountPoint`](Self::NoMatchingMountPoint) |
+    /// | 4 | [`InvalidMapping`](Self::InvalidMapping), [`ConflictingMappings`](Self::ConflictingMappings), [`InvalidRegex`](Self::InvalidRegex), [`UndefinedVariable`](Self::UndefinedVariable), [`UnknownProfile`](Self::UnknownProfile) |
+    /// | 5 | [`JsonError`](Self::JsonError), [`Json5Error`](Self::Json5Error), [`TomlError`](Self::TomlError), [`YamlError`](Self::YamlError), [`UnsupportedVersion`](Self::UnsupportedVersion), [`IncludeCycle`](Self::IncludeCycle), [`IncludeTooDeep`](Self::IncludeTooDeep) |
+    /// | 6 | [`IoError`](Self::IoError) |
+    /// | 7 | [`MountUnavailable`](Self::MountUnavailable) |
+    ///
+    /// [`ConfigError`](Self::ConfigError) uses the code of the error it wraps.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::InvalidFormat(_) | Self::MissingShare(_) | Self::UnsupportedScheme { .. } => 2,
+            Self::MappingNotFound { .. }
+            | Self::DriveNotMapped(_)
+            | Self::NotOnDrive { .. }
+            | Self::NoMatchingMountPoint(_) => 3,
+            Self::InvalidMapping(_)
+            | Self::ConflictingMappings(_)
+            | Self::InvalidRegex(_)
+            | Self::UndefinedVariable { .. }
+            | Self::UnknownProfile { .. } => 4,
+            Self::JsonError(_)
+            | Self::Json5Error(_)
+            | Self::TomlError(_)
+            | Self::YamlError(_)
+            | Self::UnsupportedVersion(_)
+            | Self::IncludeCycle(_)
+            | Self::IncludeTooDeep(_) => 5,
+            Self::IoError(_) => 6,
+            Self::MountUnavailable(_) => 7,
+            Self::ConfigError { source, .. } => source.exit_code(),
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, UncPathError>;
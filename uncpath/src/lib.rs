@@ -0,0 +1,29 @@
+//! Resolve Windows/SMB UNC paths to local POSIX mount points.
+//!
+//! ```no_run
+//! use uncpath::{convert_to_posix, MappingTable};
+//!
+//! let table = MappingTable::with_defaults();
+//! let local = convert_to_posix(r"\\server\shared\docs\a.txt", &table)?;
+//! assert_eq!(local, "/mnt/shared/docs/a.txt");
+//! # Ok::<(), uncpath::UncPathError>(())
+//! ```
+
+pub mod config;
+pub mod convert;
+pub mod drive;
+pub mod errors;
+pub mod mapping;
+
+pub use config::MappingConfig;
+pub use convert::{
+    convert_explained, convert_explained_as, convert_many, convert_to, convert_to_file_uri,
+    convert_to_nfs_url, convert_to_posix, convert_to_posix_detailed, convert_to_posix_path,
+    convert_to_smb_url, convert_to_unc, parse_input, parse_unc_path, parse_unc_path_as,
+    parse_unc_path_ref, Explanation, InputFormat, Separator, Target, UncParser, UncPath,
+    UncPathRef,
+};
+pub use errors::{Result, UncPathError};
+pub use mapping::{
+    HostForm, MappingSource, MappingTable, MergePolicy, MountMapping, OverridePolicy,
+};
@@ -1,57 +1,1304 @@
+mod tui;
+
 use anyhow::Result;
-use crossterm::{
-    event::{self, Event, KeyCode, KeyEventKind},
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-    ExecutableCommand,
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::{CommandFactory, Parser, ValueEnum};
+use clap_complete::Shell;
+use rayon::prelude::*;
+use rayon::ThreadPool;
+use serde::Serialize;
+use std::io::{self, BufRead, BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use uncpath::convert::{
+    canonicalize, redact_credentials, to_file_uri, to_nfs_url, to_posix, to_smb_url, to_unc,
 };
-use ratatui::{
-    prelude::*,
-    widgets::{Block, Borders, Paragraph},
+use uncpath::{
+    convert_explained, convert_explained_as, convert_to_unc, drive, parse_input, parse_unc_path,
+    Explanation, HostForm, InputFormat, MappingTable, MountMapping, OverridePolicy, Separator,
+    Target, UncPath, UncPathError,
 };
-use std::io;
+
+/// Mirrors [`UncPathError::exit_code`].
+const EXIT_CODES_HELP: &str = "\
+Exit codes:
+  0  success
+  1  other failure
+  2  invalid UNC path format, unsupported URL scheme, or invalid arguments
+  3  no mapping found
+  4  invalid mapping
+  5  unreadable mapping file
+  6  I/O error
+  7  mount point unavailable (--strict)
+With several inputs the code of the first failure is used.";
+
+/// Resolve UNC paths to local mount points. Starts the TUI when no path is given.
+#[derive(Parser, Debug)]
+#[command(name = "unc", version, about, after_help = EXIT_CODES_HELP)]
+struct Args {
+    /// UNC paths to convert (\\host\share\path, //host/share/path, smb://, nfs:// or file:// URL)
+    path: Vec<String>,
+
+    /// Extra mapping in the form host/share[/subpath]:/mount/point (repeatable)
+    #[arg(short, long, value_name = "MAPPING")]
+    mapping: Vec<String>,
+
+    /// Map a network drive to a share, as X=\\server\share, so X:\ paths convert (repeatable)
+    #[arg(long, value_name = "DRIVE")]
+    drive: Vec<String>,
+
+    /// Load mappings from a JSON, TOML or YAML file, or every such file in a directory
+    #[arg(short, long, value_name = "PATH")]
+    file: Option<PathBuf>,
+
+    /// Remove the mapping for host/share[/subpath] after loading (repeatable)
+    #[arg(long, value_name = "HOST/SHARE")]
+    remove: Vec<String>,
+
+    /// Profile to load from config files that define profiles [default: default]
+    #[arg(long, value_name = "NAME")]
+    profile: Option<String>,
+
+    /// Only list and convert with mappings tagged TAG
+    #[arg(long, value_name = "TAG")]
+    tag: Option<String>,
+
+    /// Match host, share and subpath names case-sensitively
+    #[arg(long)]
+    case_sensitive: bool,
+
+    /// Compare internationalized host names as punycode (ascii) or Unicode
+    #[arg(long, value_name = "FORM", value_parser = host_form_parser(), default_value = "ascii")]
+    host_form: HostForm,
+
+    /// Let a short host name like FILESERVER match a mapping for fileserver.corp.example
+    #[arg(long)]
+    match_short_names: bool,
+
+    /// Do not load the default mappings (built in, or from $UNCPATH_DEFAULTS)
+    #[arg(long)]
+    no_defaults: bool,
+
+    /// Environment variable to read extra mappings from
+    #[arg(long, value_name = "NAME", default_value = uncpath::mapping::ENV_MAPPINGS)]
+    env_var: String,
+
+    /// Use mount points verbatim instead of expanding ~ and $VAR
+    #[arg(long)]
+    no_expand: bool,
+
+    /// Strip leading and trailing whitespace from each path segment
+    #[arg(long)]
+    trim_components: bool,
+
+    /// Lowercase the whole POSIX output; may not exist on case-sensitive file systems
+    #[arg(long)]
+    lowercase_output: bool,
+
+    /// Reject //host/share inputs as local paths; smb://, file:// and nfs:// URLs still parse
+    #[arg(long)]
+    no_unix_unc: bool,
+
+    /// Accept mount points that are not absolute paths
+    #[arg(long)]
+    allow_relative: bool,
+
+    /// Allow comments and trailing commas in JSON config files (read as JSON5)
+    #[arg(long)]
+    allow_comments: bool,
+
+    /// Fail when the mount point does not exist on this machine
+    #[arg(long)]
+    strict: bool,
+
+    /// Warn when a mapping overrides an earlier one with a different mount point (an error with --strict)
+    #[arg(long)]
+    append_only: bool,
+
+    /// Resolve symlinks in the converted path; fails if it does not exist
+    #[arg(long)]
+    canonicalize: bool,
+
+    /// Like --canonicalize, but normalize lexically when the path does not exist
+    #[arg(long)]
+    canonicalize_lenient: bool,
+
+    /// Map unmapped C$-style admin shares to DIR/<host>/<drive>
+    #[arg(long, value_name = "DIR")]
+    admin_share_root: Option<PathBuf>,
+
+    /// Resolve relative mount points against DIR
+    #[arg(long, value_name = "DIR")]
+    base_dir: Option<PathBuf>,
+
+    /// Do not load ~/.config/uncpath/mappings.json (or the platform equivalent)
+    #[arg(long)]
+    no_config: bool,
+
+    /// Write the assembled mapping table to FILE as JSON
+    #[arg(long, value_name = "FILE")]
+    save: Option<PathBuf>,
+
+    /// Check the assembled mappings for conflicts and exit
+    #[arg(long)]
+    check: bool,
+
+    /// Load and validate mapping FILE or directory on its own, without
+    /// defaults or other sources, and exit (repeatable)
+    #[arg(long, value_name = "FILE")]
+    check_config: Vec<PathBuf>,
+
+    /// List the configured mappings and exit
+    #[arg(short, long)]
+    list: bool,
+
+    /// Only list mappings whose host or share contains SUBSTR (ignoring case)
+    #[arg(long, value_name = "SUBSTR", requires = "list")]
+    filter: Option<String>,
+
+    /// Sort --list output by host, share and mount point
+    #[arg(long, requires = "list")]
+    sort: bool,
+
+    /// Layout of --list output
+    #[arg(long, value_enum, default_value_t = ListFormat::Table)]
+    list_format: ListFormat,
+
+    /// Convert a local POSIX path back to UNC form
+    #[arg(short, long, conflicts_with = "to")]
+    reverse: bool,
+
+    /// Convert to WSL /mnt/<drive> paths, via --drive for UNC input; with --reverse, back to X:\
+    #[arg(long, conflicts_with = "to")]
+    wsl: bool,
+
+    /// Like --wsl, for Cygwin paths below the cygdrive prefix
+    #[arg(long, conflicts_with_all = ["to", "wsl"])]
+    cygwin: bool,
+
+    /// Cygdrive prefix for --cygwin, as set in Cygwin's /etc/fstab (MSYS2 uses /)
+    #[arg(long, value_name = "PREFIX", default_value = drive::CYGDRIVE_PREFIX)]
+    cygdrive_prefix: String,
+
+    /// Output representation; only posix consults the mapping table
+    #[arg(long, value_parser = target_parser(), default_value = "posix")]
+    to: Target,
+
+    /// Parse inputs as this format instead of detecting it, failing on
+    /// inputs of another shape
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = InputKind::Auto,
+        conflicts_with_all = ["reverse", "wsl", "cygwin"]
+    )]
+    input_format: InputKind,
+
+    /// Read paths from standard input, one per line
+    #[arg(long, conflicts_with = "path")]
+    stdin: bool,
+
+    /// Read and write NUL-delimited records instead of lines, like `xargs -0`
+    #[arg(short = '0', long, requires = "stdin", conflicts_with = "path")]
+    null: bool,
+
+    /// Convert batches on N threads (0 = one per CPU); output keeps input order
+    #[arg(short, long, value_name = "N", default_value_t = 1)]
+    jobs: usize,
+
+    /// Number of conversions the TUI keeps in its history
+    #[arg(long, value_name = "N", default_value_t = 100)]
+    history_size: usize,
+
+    /// Also copy the result (the last successful one for several inputs) to the clipboard
+    #[arg(long)]
+    copy: bool,
+
+    /// Trace the parsed path and the matching mapping on stderr
+    #[arg(short, long)]
+    verbose: bool,
+
+    /// Print nothing; report the outcome through the exit code only
+    #[arg(short, long, conflicts_with_all = ["verbose", "list"])]
+    quiet: bool,
+
+    /// Show each step of the conversion instead of just the result
+    #[arg(long, requires = "path", conflicts_with_all = ["to", "reverse", "wsl", "cygwin", "quiet", "format", "with_input"])]
+    explain: bool,
+
+    /// List every mapping that could match each path, best first, instead of converting
+    #[arg(long, requires = "path", conflicts_with_all = ["explain", "to", "reverse", "wsl", "cygwin", "quiet", "with_input"])]
+    all_matches: bool,
+
+    /// Print INPUT<TAB>OUTPUT per conversion (INPUT<TAB>ERROR on stderr)
+    #[arg(long, conflicts_with = "list")]
+    with_input: bool,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = Format::Plain)]
+    format: Format,
+
+    /// Separator for converted paths [default: posix, or windows with --reverse]
+    #[arg(long, value_parser = separator_parser())]
+    separator: Option<Separator>,
+
+    /// Print a completion script for SHELL and exit
+    #[arg(long, value_name = "SHELL", hide = true)]
+    completions: Option<Shell>,
+}
+
+fn separator_parser() -> impl TypedValueParser<Value = Separator> {
+    PossibleValuesParser::new(["posix", "windows"]).map(|value| match value.as_str() {
+        "windows" => Separator::Windows,
+        _ => Separator::Posix,
+    })
+}
+
+fn host_form_parser() -> impl TypedValueParser<Value = HostForm> {
+    PossibleValuesParser::new(["ascii", "unicode"]).map(|value| match value.as_str() {
+        "unicode" => HostForm::Unicode,
+        _ => HostForm::Ascii,
+    })
+}
+
+fn target_parser() -> impl TypedValueParser<Value = Target> {
+    PossibleValuesParser::new(["posix", "unc", "smb-url", "file-uri", "nfs-url"]).map(|value| {
+        match value.as_str() {
+            "unc" => Target::Unc,
+            "smb-url" => Target::SmbUrl,
+            "file-uri" => Target::FileUri,
+            "nfs-url" => Target::NfsUrl,
+            _ => Target::Posix,
+        }
+    })
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum InputKind {
+    /// Detect the format of each input
+    Auto,
+    /// \\host\share\path
+    Windows,
+    /// //host/share/path
+    Unix,
+    /// smb://host/share/path
+    Smb,
+    /// file://host/share/path
+    File,
+    /// nfs://host/export/path
+    Nfs,
+}
+
+impl InputKind {
+    /// The format to force, `None` to detect.
+    fn format(self) -> Option<InputFormat> {
+        match self {
+            Self::Auto => None,
+            Self::Windows => Some(InputFormat::Windows),
+            Self::Unix => Some(InputFormat::Unix),
+            Self::Smb => Some(InputFormat::Url("smb".to_string())),
+            Self::File => Some(InputFormat::Url("file".to_string())),
+            Self::Nfs => Some(InputFormat::Url("nfs".to_string())),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum ListFormat {
+    /// Aligned columns with a header
+    Table,
+    /// One "\\host\share -> mount (source)" line per mapping
+    Plain,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Format {
+    Plain,
+    Json,
+}
+
+/// One conversion as emitted by `--format json`.
+#[derive(Debug, Default, Serialize)]
+struct Record {
+    input: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    host: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    share: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    posix: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unc: Option<String>,
+    /// Drive-letter path, from `--wsl` or `--cygwin` with `--reverse`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    windows: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    smb_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file_uri: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nfs_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    suggestion: Option<String>,
+    /// Exit status for the error, 0 on success.
+    #[serde(skip)]
+    exit_code: i32,
+}
+
+/// Candidates for one input as emitted by `--all-matches --format json`.
+#[derive(Debug, Serialize)]
+struct MatchesEntry<'a> {
+    input: String,
+    matches: Vec<ListEntry<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// One mapping as emitted by `--list --format json`.
+#[derive(Debug, Serialize)]
+struct ListEntry<'a> {
+    host: &'a str,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    aliases: &'a [String],
+    share: &'a str,
+    #[serde(skip_serializing_if = "str::is_empty")]
+    subpath: &'a str,
+    mount_point: &'a str,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    regex: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<&'a str>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    tags: &'a [String],
+    source: String,
+}
+
+impl<'a> From<&'a MountMapping> for ListEntry<'a> {
+    fn from(m: &'a MountMapping) -> Self {
+        Self {
+            host: &m.host,
+            aliases: &m.aliases,
+            share: &m.share,
+            subpath: &m.subpath,
+            mount_point: &m.mount_point,
+            regex: m.regex,
+            description: m.description.as_deref(),
+            tags: &m.tags,
+            source: m.source().to_string(),
+        }
+    }
+}
 
 fn main() -> Result<()> {
-    enable_raw_mode()?;
-    io::stdout().execute(EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(io::stdout());
-    let mut terminal = Terminal::new(backend)?;
+    let args = Args::parse();
 
-    if let Err(e) = run_app(&mut terminal) {
-        cleanup_terminal(&mut terminal)?; // 出错也要恢复
-        return Err(e);
+    if let Some(shell) = args.completions {
+        clap_complete::generate(shell, &mut Args::command(), "unc", &mut io::stdout());
+        return Ok(());
     }
 
-    cleanup_terminal(&mut terminal)?;
-    Ok(())
+    if !args.check_config.is_empty() {
+        std::process::exit(check_config(&args));
+    }
+
+    if args.list || args.check || args.stdin || args.save.is_some() || !args.path.is_empty() {
+        match run(&args) {
+            Ok(0) => return Ok(()),
+            Ok(code) => std::process::exit(code),
+            Err(e) => {
+                if !args.quiet {
+                    eprintln!("Error: {}", e);
+                }
+                std::process::exit(e.exit_code());
+            }
+        }
+    }
+
+    match build_table(&args) {
+        Ok((table, warnings)) => {
+            let reload = tui::Reload {
+                paths: watched_paths(&args),
+                load: Box::new(|| build_table(&args)),
+            };
+            tui::run_tui(table, warnings, args.history_size, reload)
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(e.exit_code());
+        }
+    }
 }
 
-fn cleanup_terminal<B: Backend>(terminal: &mut Terminal<B>) -> Result<()> {
-    disable_raw_mode()?;
-    io::stdout().execute(LeaveAlternateScreen)?;
-    terminal.show_cursor()?;
-    Ok(())
+/// Files `build_table` reads, for the TUI to watch.
+fn watched_paths(args: &Args) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    if !args.no_defaults {
+        paths.extend(
+            std::env::var_os(uncpath::mapping::ENV_DEFAULTS)
+                .filter(|p| !p.is_empty())
+                .map(PathBuf::from),
+        );
+    }
+    if !args.no_config {
+        paths.extend(MappingTable::default_config_path());
+    }
+    paths.extend(args.file.clone());
+    paths
 }
 
-fn run_app<B: Backend>(terminal: &mut Terminal<B>) -> Result<()> {
-    loop {
-        terminal.draw(|f| {
-            let area = f.size();
-            let block = Block::default().title(" unc ").borders(Borders::ALL);
-            let text = Paragraph::new("Hello World")
-                .block(block)
-                .alignment(Alignment::Center);
-            f.render_widget(text, area);
+/// Assemble the mapping table: defaults < config < env < file < CLI.
+/// Warnings are returned rather than printed (none with --quiet), so the
+/// TUI can show them without writing over its screen.
+fn build_table(args: &Args) -> uncpath::Result<(MappingTable, Vec<String>)> {
+    let mut warnings = Vec::new();
+    let mut table = if args.no_defaults {
+        MappingTable::new()
+    } else {
+        MappingTable::try_with_defaults()?
+    };
+    configure(args, &mut table);
+
+    if !args.no_config {
+        table.load_default_config()?;
+    }
+    table.load_from_env_var(&args.env_var)?;
+    if let Some(file) = &args.file {
+        load_path(&mut table, file)?;
+    }
+    for spec in &args.mapping {
+        table.add_from_cli(spec)?;
+    }
+    for spec in &args.drive {
+        table.add_drive_from_cli(spec)?;
+    }
+    for target in &args.remove {
+        let (host, share) = target.split_once('/').ok_or_else(|| {
+            UncPathError::InvalidMapping(format!("expected host/share, got '{}'", target))
         })?;
+        if !table.remove_mapping(host, share) && !args.quiet {
+            warnings.push(format!("no mapping for {} to remove", target));
+        }
+    }
+    if let Some(tag) = &args.tag {
+        table.retain_tagged(tag);
+    }
+    for (shadowed, mapping) in table.take_overrides() {
+        if !args.quiet {
+            warnings.push(format!(
+                "{} -> {} ({}) overrides {} ({})",
+                mapping.unc_prefix(),
+                mapping.mount_point,
+                mapping.source(),
+                shadowed.mount_point,
+                shadowed.source()
+            ));
+        }
+    }
+
+    Ok((table, warnings))
+}
+
+/// Apply the matching and conversion settings from `args` to `table`.
+fn configure(args: &Args, table: &mut MappingTable) {
+    table.set_case_sensitive(args.case_sensitive);
+    table.set_host_form(args.host_form);
+    table.set_match_short_names(args.match_short_names);
+    table.set_expand_mount_points(!args.no_expand);
+    table.set_allow_relative(args.allow_relative);
+    table.set_trim_components(args.trim_components);
+    table.set_lowercase_output(args.lowercase_output);
+    table.set_unix_style_unc(!args.no_unix_unc);
+    table.set_allow_comments(args.allow_comments);
+    table.set_profile(args.profile.clone());
+    table.set_base_dir(args.base_dir.clone());
+    table.set_strict(args.strict);
+    table.set_override_policy(match (args.append_only, args.strict) {
+        (false, _) => OverridePolicy::Allow,
+        (true, false) => OverridePolicy::Warn,
+        (true, true) => OverridePolicy::Deny,
+    });
+    table.set_admin_share_root(args.admin_share_root.clone());
+}
+
+/// Load a mapping file, or every mapping file in a directory.
+fn load_path(table: &mut MappingTable, path: &Path) -> uncpath::Result<()> {
+    if path.is_dir() {
+        table.load_from_dir(path)
+    } else {
+        table.load_from_file(path)
+    }
+}
+
+/// Validate each --check-config file in a table of its own, printing a
+/// summary per file. Returns the exit status of the first failure.
+fn check_config(args: &Args) -> i32 {
+    let mut status = 0;
+    for file in &args.check_config {
+        let mut table = MappingTable::new();
+        configure(args, &mut table);
+        let result = load_path(&mut table, file).and_then(|()| table.validate());
+        match result {
+            Ok(()) if !args.quiet => {
+                println!("{}: {} mappings, no problems", file.display(), table.len())
+            }
+            Ok(()) => {}
+            Err(e) => {
+                if !args.quiet {
+                    match e {
+                        // Already names the file.
+                        UncPathError::ConfigError { .. } => eprintln!("Error: {}", e),
+                        _ => eprintln!("{}: {}", file.display(), e),
+                    }
+                }
+                if status == 0 {
+                    status = e.exit_code();
+                }
+            }
+        }
+    }
+    status
+}
+
+fn convert(args: &Args, table: &MappingTable, input: &str) -> uncpath::Result<String> {
+    if let Some(prefix) = drive_prefix(args) {
+        return drive_mount(args, table, input, prefix).map(|path| render(args, path));
+    }
+    if args.reverse {
+        return Ok(render(args, convert_to_unc(input, table)?));
+    }
+
+    let unc = parse(args, table, input)?;
+    Ok(match args.to {
+        Target::Posix => render(args, posix(args, input, &unc, table)?),
+        Target::Unc => render(args, to_unc(&unc)),
+        Target::SmbUrl => to_smb_url(&unc),
+        Target::FileUri => to_file_uri(&unc),
+        Target::NfsUrl => to_nfs_url(&unc),
+    })
+}
+
+/// Whether converting with `args` looks paths up in the mapping table, as
+/// opposed to only reformatting them or going through --drive mappings.
+fn needs_mappings(args: &Args, table: &MappingTable) -> bool {
+    drive_prefix(args).is_none()
+        && (args.reverse || args.to == Target::Posix)
+        && table.admin_share_root().is_none()
+}
+
+/// Parse `input` as --input-format says, detecting the format by default.
+fn parse(args: &Args, table: &MappingTable, input: &str) -> uncpath::Result<UncPath> {
+    match args.input_format.format() {
+        Some(format) => table.parser().parse_as(input, &format),
+        None => parse_input(input, table),
+    }
+}
+
+/// Where drives are mounted for --wsl or --cygwin, `None` otherwise.
+fn drive_prefix(args: &Args) -> Option<&str> {
+    if args.wsl {
+        Some(drive::WSL_PREFIX)
+    } else if args.cygwin {
+        Some(&args.cygdrive_prefix)
+    } else {
+        None
+    }
+}
+
+/// Convert to a path below the drive mount `prefix`, or from one with
+/// --reverse.
+fn drive_mount(
+    args: &Args,
+    table: &MappingTable,
+    input: &str,
+    prefix: &str,
+) -> uncpath::Result<String> {
+    if args.reverse {
+        drive::from_cygdrive(input, prefix)
+            .ok_or_else(|| UncPathError::NoMatchingMountPoint(input.trim().to_string()))
+    } else {
+        drive::convert_to_cygdrive(input, table, prefix)
+    }
+}
+
+/// Map `unc`, parsed from `input`, to a local path, canonicalizing it when
+/// asked to.
+fn posix(args: &Args, input: &str, unc: &UncPath, table: &MappingTable) -> uncpath::Result<String> {
+    if args.verbose {
+        trace(args, table, input);
+    }
+    let path = to_posix(unc, table)?;
+    if args.canonicalize || args.canonicalize_lenient {
+        canonicalize(&path, args.canonicalize_lenient)
+    } else {
+        Ok(path)
+    }
+}
+
+/// Show on stderr how `input` is looked up, for --verbose. Goes through
+/// the same resolution as the conversion itself, so trimmed components and
+/// administrative shares are reported as they are matched.
+fn trace(args: &Args, table: &MappingTable, input: &str) {
+    let Ok(explanation) = explained(args, table, input) else {
+        return;
+    };
+    let unc = &explanation.unc;
+    eprintln!(
+        "parsed: host={} share={} path={}",
+        unc.host, unc.share, explanation.normalized_path
+    );
+    match (&explanation.mapping, &explanation.mount_point) {
+        (Some(m), _) => eprintln!(
+            "matched: {} -> {} ({})",
+            m.unc_prefix(),
+            m.mount_point,
+            m.source
+        ),
+        (None, Some(mount_point)) => {
+            eprintln!("matched: administrative share root -> {}", mount_point)
+        }
+        (None, None) => eprintln!("matched: nothing"),
+    }
+}
+
+fn render(args: &Args, path: String) -> String {
+    match args.separator {
+        Some(separator) => separator.render(&path),
+        None => path,
+    }
+}
+
+/// "did you mean" text for a lookup that found no mapping.
+fn suggestion(table: &MappingTable, err: &UncPathError) -> Option<String> {
+    match err {
+        UncPathError::MappingNotFound { host, share } => table
+            .suggest(host, share)
+            .map(|m| format!("did you mean {}?", m.unc_prefix())),
+        _ => None,
+    }
+}
+
+/// Write a successful conversion to `out`, after its input with
+/// `--with-input`.
+fn print_output(args: &Args, out: &mut impl Write, input: &str, output: &str) -> io::Result<()> {
+    if args.quiet {
+        return Ok(());
+    }
+    let terminator = if args.null { '\0' } else { '\n' };
+    if args.with_input {
+        write!(
+            out,
+            "{}\t{}{}",
+            redact_credentials(input),
+            output,
+            terminator
+        )
+    } else {
+        write!(out, "{}{}", output, terminator)
+    }
+}
+
+/// Print a failed conversion on stderr, with a "did you mean" hint when
+/// there is one. `--with-input` prints a bare `INPUT<TAB>ERROR` line.
+fn report(args: &Args, table: &MappingTable, input: Option<&str>, err: &UncPathError) {
+    if args.quiet {
+        return;
+    }
+    match input {
+        Some(input) if args.with_input => {
+            eprintln!("{}\t{}", redact_credentials(input), err);
+            return;
+        }
+        Some(input) => eprintln!("{}: {}", redact_credentials(input), err),
+        None => eprintln!("Error: {}", err),
+    }
+    if let Some(hint) = suggestion(table, err) {
+        eprintln!("  {}", hint);
+    }
+}
+
+fn convert_record(args: &Args, table: &MappingTable, input: &str) -> Record {
+    let mut record = Record {
+        input: redact_credentials(input).into_owned(),
+        ..Default::default()
+    };
+
+    if let Some(prefix) = drive_prefix(args) {
+        match drive_mount(args, table, input, prefix) {
+            Ok(path) if args.reverse => record.windows = Some(render(args, path)),
+            Ok(path) => record.posix = Some(render(args, path)),
+            Err(e) => record.fail(&e),
+        }
+        return record;
+    }
 
-        if let Event::Key(key) = event::read()? {
-            if key.kind == KeyEventKind::Press {
-                match key.code {
-                    KeyCode::Char('q') | KeyCode::Esc => break,
-                    _ => {}
+    if args.reverse {
+        match convert_to_unc(input, table) {
+            Ok(unc) => {
+                if let Ok(parsed) = parse_unc_path(&unc) {
+                    record.host = Some(parsed.host);
+                    record.share = Some(parsed.share);
                 }
+                record.unc = Some(render(args, unc));
             }
+            Err(e) => record.fail(&e),
         }
+        return record;
     }
+
+    let unc = match parse(args, table, input) {
+        Ok(unc) => unc,
+        Err(e) => {
+            record.fail(&e);
+            return record;
+        }
+    };
+    match args.to {
+        Target::Posix => match posix(args, input, &unc, table) {
+            Ok(posix) => record.posix = Some(render(args, posix)),
+            Err(e) => {
+                record.suggestion = suggestion(table, &e);
+                record.fail(&e);
+            }
+        },
+        Target::Unc => record.unc = Some(render(args, to_unc(&unc))),
+        Target::SmbUrl => record.smb_url = Some(to_smb_url(&unc)),
+        Target::FileUri => record.file_uri = Some(to_file_uri(&unc)),
+        Target::NfsUrl => record.nfs_url = Some(to_nfs_url(&unc)),
+    }
+    record.host = Some(unc.host);
+    record.share = Some(unc.share);
+
+    record
+}
+
+impl Record {
+    fn fail(&mut self, err: &UncPathError) {
+        self.error = Some(err.to_string());
+        self.exit_code = err.exit_code();
+    }
+
+    /// The converted path, whichever target produced it.
+    fn output(&self) -> Option<&str> {
+        [
+            &self.posix,
+            &self.unc,
+            &self.windows,
+            &self.smb_url,
+            &self.file_uri,
+            &self.nfs_url,
+        ]
+        .into_iter()
+        .find_map(Option::as_deref)
+    }
+}
+
+fn print_json<T: Serialize>(value: &T) -> uncpath::Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
     Ok(())
 }
 
+/// Print mappings as left-aligned columns sized to their widest cell.
+fn print_mapping_table(mappings: &[&MountMapping]) {
+    let header = [
+        "HOST",
+        "SHARE",
+        "MOUNT POINT",
+        "SOURCE",
+        "TAGS",
+        "DESCRIPTION",
+    ]
+    .map(String::from);
+    let rows: Vec<[String; 6]> = std::iter::once(header)
+        .chain(mappings.iter().map(|m| {
+            let share = if m.subpath.is_empty() {
+                m.share.clone()
+            } else {
+                format!("{}/{}", m.share, m.subpath)
+            };
+            let host = if m.aliases.is_empty() {
+                m.host.clone()
+            } else {
+                format!("{} ({})", m.host, m.aliases.join(", "))
+            };
+            [
+                host,
+                share,
+                m.mount_point.clone(),
+                m.source().to_string(),
+                m.tags.join(","),
+                m.description.clone().unwrap_or_default(),
+            ]
+        }))
+        .collect();
+
+    let mut widths = [0; 6];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    for row in &rows {
+        let line = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ");
+        println!("{}", line.trim_end());
+    }
+}
+
+/// Returns the exit status: 0, or the code of the first failed
+/// conversion.
+fn run(args: &Args) -> uncpath::Result<i32> {
+    let (mut table, warnings) = build_table(args)?;
+    for warning in &warnings {
+        eprintln!("Warning: {}", warning);
+    }
+
+    if let Some(file) = &args.save {
+        table.save_to_file(file)?;
+    }
+
+    if args.check {
+        table.validate()?;
+        if !args.quiet {
+            println!("{} mappings, no conflicts", table.len());
+        }
+        return Ok(0);
+    }
+
+    if args.list {
+        if args.sort {
+            table.sort();
+        }
+        let filter = args.filter.as_deref().map(str::to_lowercase);
+        let mappings: Vec<&MountMapping> = table
+            .iter()
+            .filter(|m| match &filter {
+                Some(needle) => {
+                    m.host.to_lowercase().contains(needle)
+                        || m.share.to_lowercase().contains(needle)
+                }
+                None => true,
+            })
+            .collect();
+
+        if args.format == Format::Json {
+            let entries: Vec<ListEntry> = mappings.into_iter().map(ListEntry::from).collect();
+            print_json(&entries)?;
+            return Ok(0);
+        }
+        if table.is_empty() {
+            println!("No mappings configured.");
+            return Ok(0);
+        }
+        match args.list_format {
+            ListFormat::Table => print_mapping_table(&mappings),
+            ListFormat::Plain => {
+                println!("Configured mappings:");
+                for m in mappings {
+                    match &m.description {
+                        Some(description) => println!(
+                            "  {} -> {} ({}): {}",
+                            m.unc_prefix(),
+                            m.mount_point,
+                            m.source(),
+                            description
+                        ),
+                        None => {
+                            println!("  {} -> {} ({})", m.unc_prefix(), m.mount_point, m.source())
+                        }
+                    }
+                }
+            }
+        }
+        return Ok(0);
+    }
+
+    if table.is_empty() && needs_mappings(args, &table) && !args.quiet {
+        eprintln!("Warning: no mappings configured; add --mapping or --file");
+    }
+
+    if args.explain {
+        let mut status = 0;
+        for (i, path) in args.path.iter().enumerate() {
+            if i > 0 {
+                println!();
+            }
+            let code = explain(args, &table, path);
+            if status == 0 {
+                status = code;
+            }
+        }
+        return Ok(status);
+    }
+
+    if args.all_matches {
+        return all_matches(args, &table);
+    }
+
+    if args.stdin {
+        let separator = if args.null { b'\0' } else { b'\n' };
+        return run_batch(args, &table, io::stdin().lock().split(separator));
+    }
+
+    if let [path] = args.path.as_slice() {
+        if args.format == Format::Json {
+            let record = convert_record(args, &table, path);
+            if !args.quiet {
+                print_json(&record)?;
+            }
+            if let (true, Some(output)) = (args.copy, record.output()) {
+                copy_to_clipboard(args, output);
+            }
+            return Ok(record.exit_code);
+        }
+        return match convert(args, &table, path) {
+            Ok(result) => {
+                print_output(args, &mut io::stdout().lock(), path, &result)?;
+                if args.copy {
+                    copy_to_clipboard(args, &result);
+                }
+                Ok(0)
+            }
+            Err(e) => {
+                report(args, &table, args.with_input.then_some(path.as_str()), &e);
+                Ok(e.exit_code())
+            }
+        };
+    }
+
+    run_batch(
+        args,
+        &table,
+        args.path.iter().map(|path| Ok(path.clone().into_bytes())),
+    )
+}
+
+/// Print the candidate mappings for each path. Returns the exit status
+/// of the first path that fails to parse or has no candidates.
+fn all_matches(args: &Args, table: &MappingTable) -> uncpath::Result<i32> {
+    let mut status = 0;
+    let mut entries = Vec::new();
+    for input in &args.path {
+        let result = parse(args, table, input).and_then(|unc| {
+            let matches = table.find_all_mount_points(&unc.host, &unc.share);
+            if matches.is_empty() {
+                Err(UncPathError::MappingNotFound {
+                    host: unc.host,
+                    share: unc.share,
+                })
+            } else {
+                Ok(matches)
+            }
+        });
+        if let Err(e) = &result {
+            if status == 0 {
+                status = e.exit_code();
+            }
+        }
+
+        if args.format == Format::Json {
+            let (matches, error) = match result {
+                Ok(matches) => (matches.into_iter().map(ListEntry::from).collect(), None),
+                Err(e) => (Vec::new(), Some(e.to_string())),
+            };
+            entries.push(MatchesEntry {
+                input: redact_credentials(input).into_owned(),
+                matches,
+                error,
+            });
+            continue;
+        }
+        match result {
+            Ok(matches) => {
+                println!("{}:", redact_credentials(input));
+                for m in matches {
+                    println!("  {} -> {} ({})", m.unc_prefix(), m.mount_point, m.source());
+                }
+            }
+            Err(e) => report(args, table, Some(input), &e),
+        }
+    }
+    if args.format == Format::Json {
+        print_json(&entries)?;
+    }
+    Ok(status)
+}
+
+/// The steps of converting `input`, parsed as --input-format says.
+fn explained(args: &Args, table: &MappingTable, input: &str) -> uncpath::Result<Explanation> {
+    match args.input_format.format() {
+        Some(format) => convert_explained_as(input, &format, table),
+        None => convert_explained(input, table),
+    }
+}
+
+/// Print the steps of converting `input` for --explain and return the
+/// exit status.
+fn explain(args: &Args, table: &MappingTable, input: &str) -> i32 {
+    let explanation = match explained(args, table, input) {
+        Ok(explanation) => explanation,
+        Err(e) => {
+            report(args, table, Some(input), &e);
+            return e.exit_code();
+        }
+    };
+    let field = |label: &str, value: &dyn std::fmt::Display| {
+        println!("{:<12} {}", format!("{}:", label), value);
+    };
+
+    let unc = &explanation.unc;
+    field("input", &redact_credentials(input));
+    field("format", &explanation.format);
+    field("host", &unc.host);
+    field("share", &unc.share);
+    field("path", &explanation.normalized_path);
+    match (&explanation.mapping, &explanation.mount_point) {
+        (Some(m), _) => field(
+            "mapping",
+            &format!("{} -> {} ({})", m.unc_prefix(), m.mount_point, m.source()),
+        ),
+        (None, Some(_)) => field("mapping", &"administrative share root"),
+        (None, None) => field("mapping", &"none"),
+    }
+    if let Some(mount_point) = &explanation.mount_point {
+        field("mount point", mount_point);
+        field("remainder", &explanation.remainder);
+    }
+    match explanation.result {
+        Ok(posix) => {
+            field("result", &render(args, posix));
+            0
+        }
+        Err(e) => {
+            field("error", &e);
+            if let Some(hint) = suggestion(table, &e) {
+                field("hint", &hint);
+            }
+            e.exit_code()
+        }
+    }
+}
+
+/// Inputs converted per round when running on several threads.
+const CHUNK_SIZE: usize = 4096;
+
+/// Convert every non-empty input in order, reporting failures on stderr
+/// without stopping. JSON output is a single array, written one record at
+/// a time.
+///
+/// With `--jobs` other than 1, inputs are read in chunks of [`CHUNK_SIZE`]
+/// and each chunk is converted on a thread pool; results are still written
+/// in input order. Otherwise each input is converted as soon as it is read.
+/// Either way memory stays bounded by the chunk, not the input. Output is
+/// buffered, except that a terminal sees each chunk as it is done; a
+/// closed stdout (e.g. `| head`) ends the run quietly.
+fn run_batch(
+    args: &Args,
+    table: &MappingTable,
+    mut inputs: impl Iterator<Item = io::Result<Vec<u8>>>,
+) -> uncpath::Result<i32> {
+    let jobs = match args.jobs {
+        0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+        jobs => jobs,
+    };
+    let pool = match jobs {
+        1 => None,
+        jobs => Some(
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(jobs)
+                .build()
+                .map_err(io::Error::other)?,
+        ),
+    };
+    let chunk_size = if pool.is_some() { CHUNK_SIZE } else { 1 };
+
+    let mut out = BufWriter::new(io::stdout().lock());
+    let mut batch = Batch {
+        json: (args.format == Format::Json && !args.quiet).then(JsonArray::default),
+        flush: io::stdout().is_terminal(),
+        status: 0,
+        last: None,
+    };
+    loop {
+        let lines = inputs
+            .by_ref()
+            .take(chunk_size)
+            .collect::<io::Result<Vec<_>>>()?;
+        if lines.is_empty() {
+            break;
+        }
+        // Records that are not UTF-8 are shown lossily and fail on their
+        // own; only read errors end the batch.
+        let lines: Vec<std::result::Result<String, String>> = lines
+            .into_iter()
+            .map(|line| {
+                String::from_utf8(line)
+                    .map_err(|e| String::from_utf8_lossy(e.as_bytes()).into_owned())
+            })
+            .collect();
+        let chunk: Vec<BatchInput> = lines
+            .iter()
+            .map(|line| match line {
+                Ok(text) => Ok(text.trim()),
+                Err(shown) => Err(shown.trim()),
+            })
+            .filter(|input| !input.is_ok_and(str::is_empty))
+            .collect();
+
+        match batch.write_chunk(args, table, pool.as_ref(), &chunk, &mut out) {
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => return Ok(batch.status),
+            result => result?,
+        }
+    }
+    match batch.finish(&mut out) {
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => return Ok(batch.status),
+        result => result?,
+    }
+    if let (true, Some(last)) = (args.copy, batch.last) {
+        copy_to_clipboard(args, &last);
+    }
+    Ok(batch.status)
+}
+
+/// Output state of [`run_batch`].
+struct Batch {
+    /// The open JSON array, unless printing text or nothing.
+    json: Option<JsonArray>,
+    /// Flush after every chunk, so a terminal sees results as they come.
+    flush: bool,
+    /// Exit status of the first failure.
+    status: i32,
+    /// Last successful output, for `--copy`.
+    last: Option<String>,
+}
+
+/// A trimmed batch input, or how a record that is not UTF-8 is shown.
+type BatchInput<'a> = std::result::Result<&'a str, &'a str>;
+
+fn not_utf8() -> UncPathError {
+    UncPathError::InvalidFormat("input is not valid UTF-8".to_string())
+}
+
+impl Batch {
+    fn write_chunk(
+        &mut self,
+        args: &Args,
+        table: &MappingTable,
+        pool: Option<&ThreadPool>,
+        chunk: &[BatchInput],
+        out: &mut impl Write,
+    ) -> io::Result<()> {
+        if args.format == Format::Json {
+            let records = map_chunk(pool, chunk, |input| match *input {
+                Ok(input) => convert_record(args, table, input),
+                Err(shown) => {
+                    let mut record = Record {
+                        input: shown.to_string(),
+                        ..Default::default()
+                    };
+                    record.fail(&not_utf8());
+                    record
+                }
+            });
+            for record in records {
+                if self.status == 0 {
+                    self.status = record.exit_code;
+                }
+                if let Some(output) = record.output() {
+                    self.last = Some(output.to_string());
+                }
+                if let Some(json) = &mut self.json {
+                    json.push(out, &record)?;
+                }
+            }
+        } else {
+            let results = map_chunk(pool, chunk, |input| match *input {
+                Ok(input) => convert(args, table, input),
+                Err(_) => Err(not_utf8()),
+            });
+            for (input, result) in chunk.iter().zip(results) {
+                let input = input.unwrap_or_else(|shown| shown);
+                match result {
+                    Ok(result) => {
+                        print_output(args, out, input, &result)?;
+                        self.last = Some(result);
+                    }
+                    Err(e) => {
+                        report(args, table, Some(input), &e);
+                        if self.status == 0 {
+                            self.status = e.exit_code();
+                        }
+                    }
+                }
+            }
+        }
+        if self.flush {
+            out.flush()?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self, out: &mut impl Write) -> io::Result<()> {
+        if let Some(json) = self.json.take() {
+            json.finish(out)?;
+        }
+        out.flush()
+    }
+}
+
+/// A JSON array written element by element, laid out exactly like
+/// `serde_json::to_string_pretty` would lay out the whole array.
+#[derive(Default)]
+struct JsonArray {
+    len: usize,
+}
+
+impl JsonArray {
+    fn push<T: Serialize>(&mut self, out: &mut impl Write, value: &T) -> io::Result<()> {
+        out.write_all(if self.len == 0 { b"[\n" } else { b",\n" })?;
+        let pretty = serde_json::to_string_pretty(value)?;
+        // Strings are escaped, so every newline is layout and the element
+        // can be indented line by line.
+        for (i, line) in pretty.lines().enumerate() {
+            if i > 0 {
+                out.write_all(b"\n")?;
+            }
+            write!(out, "  {}", line)?;
+        }
+        self.len += 1;
+        Ok(())
+    }
+
+    fn finish(self, out: &mut impl Write) -> io::Result<()> {
+        if self.len == 0 {
+            writeln!(out, "[]")
+        } else {
+            writeln!(out, "\n]")
+        }
+    }
+}
+
+/// Apply `f` to every input, on `pool` if there is one, keeping order.
+fn map_chunk<I: Sync, T: Send>(
+    pool: Option<&ThreadPool>,
+    inputs: &[I],
+    f: impl Fn(&I) -> T + Sync,
+) -> Vec<T> {
+    match pool {
+        Some(pool) => pool.install(|| inputs.par_iter().map(&f).collect()),
+        None => inputs.iter().map(f).collect(),
+    }
+}
+
+/// Put `text` on the system clipboard, warning on stderr when there is no
+/// clipboard (e.g. on a headless machine), unless `--quiet`.
+fn copy_to_clipboard(args: &Args, text: &str) {
+    let result = arboard::Clipboard::new().and_then(|mut clipboard| clipboard.set_text(text));
+    if let (Err(e), false) = (result, args.quiet) {
+        eprintln!("Warning: could not copy to clipboard: {}", e);
+    }
+}
@@ -0,0 +1,2207 @@
+use crate::config::{self, ConfigFormat, MappingConfig, MAX_INCLUDE_DEPTH};
+use crate::convert::{UncParser, DEFAULT_PARSER};
+use crate::errors::{Result, UncPathError};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::net::Ipv4Addr;
+use std::path::{Path, PathBuf};
+
+/// Environment variable holding extra mappings, separated by `;`.
+pub const ENV_MAPPINGS: &str = "UNCPATH_MAPPINGS";
+
+/// Environment variable naming a mapping file that replaces the built-in
+/// defaults, see [`MappingTable::with_defaults`].
+pub const ENV_DEFAULTS: &str = "UNCPATH_DEFAULTS";
+
+/// The built-in defaults, a [`MappingConfig`] in JSON.
+const BUILTIN_DEFAULTS: &str = include_str!("defaults.json");
+
+/// Largest edit distance [`MappingTable::suggest`] still reports.
+pub const SUGGEST_DISTANCE: usize = 2;
+
+/// Directory name under the platform config dir.
+const CONFIG_DIR: &str = "uncpath";
+
+/// File name of the auto-loaded config inside [`CONFIG_DIR`].
+const CONFIG_FILE: &str = "mappings.json";
+
+/// Where a mapping came from, for tracing precedence.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub enum MappingSource {
+    /// Added through the library API.
+    #[default]
+    Api,
+    /// The defaults of [`MappingTable::with_defaults`], built in or from
+    /// [`ENV_DEFAULTS`].
+    Default,
+    /// An environment variable such as [`ENV_MAPPINGS`].
+    Env,
+    /// A mapping file.
+    File(PathBuf),
+    /// A `--mapping` command-line spec.
+    Cli,
+}
+
+impl fmt::Display for MappingSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Api => f.write_str("api"),
+            Self::Default => f.write_str("default"),
+            Self::Env => f.write_str("env"),
+            Self::File(path) => write!(f, "file {}", path.display()),
+            Self::Cli => f.write_str("cli"),
+        }
+    }
+}
+
+/// How internationalized host names are compared. Either way both the
+/// lookup and the stored mapping are converted, so `münchen.example` and
+/// `xn--mnchen-3ya.example` match each other; the spelling a mapping was
+/// written with is kept for display.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HostForm {
+    /// Compare the ASCII (punycode) form.
+    #[default]
+    Ascii,
+    /// Compare the Unicode form.
+    Unicode,
+}
+
+impl HostForm {
+    /// `host` in this form. Plain ASCII hosts without `xn--` labels, and
+    /// hosts IDNA rejects (IP literals, globs it cannot handle), are
+    /// returned unchanged.
+    pub fn normalize<'a>(&self, host: &'a str) -> Cow<'a, str> {
+        if host.is_ascii() && !host.to_ascii_lowercase().contains("xn--") {
+            return Cow::Borrowed(host);
+        }
+        let converted = match self {
+            Self::Ascii => idna::domain_to_ascii(host).ok(),
+            Self::Unicode => match idna::domain_to_unicode(host) {
+                (unicode, Ok(())) => Some(unicode),
+                (_, Err(_)) => None,
+            },
+        };
+        converted.map_or(Cow::Borrowed(host), Cow::Owned)
+    }
+}
+
+/// How [`MappingTable::merge`] treats a mapping for a host/share/subpath
+/// the table already maps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// Keep the table's mapping and skip the incoming one.
+    KeepExisting,
+    /// Drop the table's mapping in favour of the incoming one.
+    Overwrite,
+    /// Add the incoming mapping after the existing one, which it then
+    /// shadows on lookup. This is how the CLI layers its sources.
+    #[default]
+    Append,
+}
+
+/// What [`MappingTable::add`] does with a mapping that would shadow an
+/// existing one for the same host/share/subpath with a different mount
+/// point.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverridePolicy {
+    /// Add it; the new mapping wins lookups.
+    #[default]
+    Allow,
+    /// Add it, and record the override for
+    /// [`take_overrides`](MappingTable::take_overrides).
+    Warn,
+    /// Reject it with [`UncPathError::ConflictingMappings`].
+    Deny,
+}
+
+/// A single `host/share[/subpath] -> mount_point` mapping.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MountMapping {
+    pub host: String,
+    pub share: String,
+    /// Directory below the share this mapping is rooted at, `/`-separated.
+    /// Empty when the whole share is mapped.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub subpath: String,
+    pub mount_point: String,
+    /// Treat `host` and `share` as regular expressions (matched in full,
+    /// case-insensitively) instead of literal names or globs.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub regex: bool,
+    /// Other names for `host`, e.g. the NetBIOS name of an FQDN host.
+    /// Consulted only when no mapping matches a host by its primary name.
+    /// Ignored for regex and wildcard mappings.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    /// Free-form note on what the mapping is for, shown by `--list`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Labels such as `dev` or `prod` for selecting a subset of mappings,
+    /// see [`MappingTable::retain_tagged`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Where the mapping was loaded from. Not stored in files.
+    #[serde(skip)]
+    pub source: MappingSource,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+impl MountMapping {
+    /// Create a mapping. `share` may carry a subpath, e.g. `shared/projects`.
+    pub fn new(host: &str, share: &str, mount_point: &str) -> Self {
+        let mut mapping = Self {
+            host: host.to_string(),
+            share: share.to_string(),
+            subpath: String::new(),
+            mount_point: mount_point.to_string(),
+            regex: false,
+            aliases: Vec::new(),
+            description: None,
+            tags: Vec::new(),
+            source: MappingSource::default(),
+        };
+        mapping.normalize_subpath();
+        mapping
+    }
+
+    /// Create a mapping whose host and share are regular expressions.
+    pub fn new_regex(host_pattern: &str, share_pattern: &str, mount_point: &str) -> Self {
+        Self {
+            host: host_pattern.to_string(),
+            share: share_pattern.to_string(),
+            subpath: String::new(),
+            mount_point: mount_point.to_string(),
+            regex: true,
+            aliases: Vec::new(),
+            description: None,
+            tags: Vec::new(),
+            source: MappingSource::default(),
+        }
+    }
+
+    /// Where the mapping came from.
+    pub fn source(&self) -> &MappingSource {
+        &self.source
+    }
+
+    /// Add alternative host names, see [`aliases`](Self::aliases).
+    pub fn with_aliases<I, S>(mut self, aliases: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.aliases.extend(aliases.into_iter().map(Into::into));
+        self
+    }
+
+    /// Set the [`description`](Self::description).
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Add [`tags`](Self::tags).
+    pub fn with_tags<I, S>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.tags.extend(tags.into_iter().map(Into::into));
+        self
+    }
+
+    /// Whether the mapping carries `tag`.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// Set where the mapping came from.
+    pub fn with_source(mut self, source: MappingSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Move anything after the first separator in `share` into `subpath`.
+    /// Regex shares are left alone since `\\` is meaningful there.
+    fn normalize_subpath(&mut self) {
+        let mut subpath = self.subpath.replace('\\', "/");
+        if !self.regex {
+            let share = self.share.replace('\\', "/");
+            match share.split_once('/') {
+                Some((share, extra)) => {
+                    subpath = format!("{}/{}", extra, subpath);
+                    self.share = share.to_string();
+                }
+                None => self.share = share,
+            }
+        }
+        self.subpath = subpath
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join("/");
+    }
+
+    /// The UNC prefix this mapping covers, e.g. `\\server\shared\projects`.
+    pub fn unc_prefix(&self) -> String {
+        let mut prefix = format!(r"\\{}\{}", self.host, self.share);
+        for segment in self.subpath.split('/').filter(|s| !s.is_empty()) {
+            prefix.push('\\');
+            prefix.push_str(segment);
+        }
+        prefix
+    }
+
+    /// Whether `host` or `share` contains a `*` glob.
+    pub fn is_wildcard(&self) -> bool {
+        !self.regex && (self.host.contains('*') || self.share.contains('*'))
+    }
+
+    /// If `path` lies under this mapping's subpath, return the remainder.
+    pub fn strip_subpath<'a>(&self, path: &'a str, case_sensitive: bool) -> Option<&'a str> {
+        if self.subpath.is_empty() {
+            return Some(path);
+        }
+        let n = self.subpath.len();
+        let head = path.get(..n)?;
+        let rest = &path[n..];
+        (fold(head, case_sensitive) == fold(&self.subpath, case_sensitive)
+            && (rest.is_empty() || rest.starts_with('/')))
+        .then(|| rest.trim_start_matches('/'))
+    }
+
+    /// Parse a mapping spec of the form `host/share[/subpath]:/mount/point`.
+    pub fn parse_spec(spec: &str) -> Result<Self> {
+        let invalid = || {
+            UncPathError::InvalidMapping(format!(
+                "expected host/share:/mount/point, got '{}'",
+                spec
+            ))
+        };
+
+        let (host, rest) = spec.trim().split_once('/').ok_or_else(invalid)?;
+        let (share, mount_point) = rest.split_once(':').ok_or_else(invalid)?;
+        if host.is_empty() || share.is_empty() || mount_point.is_empty() {
+            return Err(invalid());
+        }
+
+        Ok(Self::new(host, share, mount_point))
+    }
+}
+
+/// Ordered collection of mappings. Later mappings take precedence over
+/// earlier ones for the same host/share.
+///
+/// Host, share and subpath matching ignores case unless the table is made
+/// case-sensitive, e.g. for Samba servers with `case sensitive = yes`.
+#[derive(Debug, Clone, Default)]
+pub struct MappingTable {
+    mappings: Vec<MountMapping>,
+    /// Case-folded names of each mapping, parallel to `mappings`, so
+    /// lookups only fold the query. Mappings are never handed out mutably,
+    /// so these stay in sync; [`set_case_sensitive`](Self::set_case_sensitive)
+    /// rebuilds them.
+    keys: Vec<FoldedKey>,
+    /// Case-folded `(host, share)` -> the literal mappings for it. Keys are
+    /// lowercased only when matching ignores case.
+    index: HashMap<(String, String), Bucket>,
+    /// Like `index`, keyed by each alias of a literal mapping.
+    alias_index: HashMap<(String, String), Bucket>,
+    /// Like `index`, keyed by the first DNS label of dotted host names, for
+    /// short-name lookups.
+    short_index: HashMap<(String, String), Bucket>,
+    /// Positions of wildcard mappings, which cannot be indexed.
+    wildcards: Vec<usize>,
+    /// Positions of regex mappings with their compiled host/share patterns.
+    patterns: Vec<(usize, Regex, Regex)>,
+    case_sensitive: bool,
+    /// Form internationalized host names are compared in.
+    host_form: HostForm,
+    /// Let a dotless host match the first label of a mapping's FQDN.
+    match_short_names: bool,
+    /// Use mount points verbatim instead of expanding `~` and `$VAR`.
+    raw_mount_points: bool,
+    /// Strip surrounding whitespace from each path segment on conversion.
+    trim_components: bool,
+    /// Lowercase the whole converted path.
+    lowercase_output: bool,
+    /// Reject `//host/share` inputs as local paths.
+    no_unix_style_unc: bool,
+    /// Parser for inputs, when not the built-in one.
+    parser: Option<UncParser>,
+    /// Accept mount points that are not absolute paths.
+    allow_relative: bool,
+    /// Read `.json` config files as JSON5.
+    allow_comments: bool,
+    /// Profile to load from config files that define profiles.
+    profile: Option<String>,
+    /// Network drive letters, uppercased, to the host and share they map.
+    drive_mappings: HashMap<char, (String, String)>,
+    override_policy: OverridePolicy,
+    /// Overrides recorded under [`OverridePolicy::Warn`], as
+    /// `(shadowed, added)`.
+    overrides: Vec<(MountMapping, MountMapping)>,
+    /// Directory relative mount points are resolved against.
+    base_dir: Option<PathBuf>,
+    /// Require the resolved mount point to exist on this machine.
+    strict: bool,
+    /// Root for automatic `C$`-style administrative share mounts.
+    admin_share_root: Option<PathBuf>,
+}
+
+/// Host, share and subpath of a mapping as lookups compare them.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct FoldedKey {
+    host: String,
+    share: String,
+    subpath: String,
+}
+
+/// Positions in `mappings` of the literal mappings for one index key, in
+/// insertion order, also arranged by subpath.
+#[derive(Debug, Clone, Default)]
+struct Bucket {
+    positions: Vec<usize>,
+    subpaths: SubpathTrie,
+}
+
+impl Bucket {
+    fn push(&mut self, position: usize, folded_subpath: &str) {
+        self.positions.push(position);
+        self.subpaths.insert(folded_subpath, position);
+    }
+}
+
+/// Folded subpaths split into segments, so the deepest mapping prefixing a
+/// path is found in one walk down the path instead of a scan of every
+/// mapping for the share. Each node lists the mappings whose subpath ends
+/// there, in insertion order; the root holds the whole-share mappings.
+#[derive(Debug, Clone, Default)]
+struct SubpathTrie {
+    positions: Vec<usize>,
+    children: HashMap<String, SubpathTrie>,
+}
+
+impl SubpathTrie {
+    fn insert(&mut self, folded_subpath: &str, position: usize) {
+        let mut node = self;
+        for segment in folded_subpath.split('/').filter(|s| !s.is_empty()) {
+            node = node.children.entry(segment.to_string()).or_default();
+        }
+        node.positions.push(position);
+    }
+
+    /// The deepest mapping whose subpath prefixes `folded_path`, with its
+    /// depth in segments. Among mappings at the same node the last wins.
+    fn deepest(&self, folded_path: &str) -> Option<(usize, usize)> {
+        let mut best = self.positions.last().map(|&i| (i, 0));
+        let mut node = self;
+        for (depth, segment) in folded_path.split('/').enumerate() {
+            let Some(child) = node.children.get(segment) else {
+                break;
+            };
+            node = child;
+            if let Some(&i) = node.positions.last() {
+                best = Some((i, depth + 1));
+            }
+        }
+        best
+    }
+
+    /// Whether any mapping below this node is for a subpath.
+    fn has_subpaths(&self) -> bool {
+        !self.children.is_empty()
+    }
+}
+
+/// The first label of a dotted host name, unless it is an IPv4 address.
+fn short_name(host: &str) -> Option<&str> {
+    let (label, _) = host.split_once('.')?;
+    (!label.is_empty() && host.parse::<Ipv4Addr>().is_err()).then_some(label)
+}
+
+/// Lowercase `s` unless matching is case-sensitive.
+fn fold(s: &str, case_sensitive: bool) -> Cow<'_, str> {
+    if case_sensitive {
+        Cow::Borrowed(s)
+    } else {
+        Cow::Owned(s.to_lowercase())
+    }
+}
+
+/// Compile `pattern` to match a whole host or share name.
+fn compile_pattern(pattern: &str, case_sensitive: bool) -> Result<Regex> {
+    let flags = if case_sensitive { "" } else { "(?i)" };
+    // Validate the pattern on its own first so errors point at user input.
+    Regex::new(pattern)
+        .and_then(|_| Regex::new(&format!("{}^(?:{})$", flags, pattern)))
+        .map_err(|e| UncPathError::InvalidRegex(format!("'{}': {}", pattern, e)))
+}
+
+/// Whether `mount_point` is absolute, or will be once `~`/`$VAR` expand.
+fn is_absolute_mount(mount_point: &str) -> bool {
+    mount_point.starts_with(['/', '$']) || mount_point == "~" || mount_point.starts_with("~/")
+}
+
+/// Glob match where `*` matches any run of characters. Both sides must
+/// already be folded alike.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack = None;
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == text[t] {
+            p += 1;
+            t += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Edit distance between `a` and `b`, counted in characters.
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitute = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitute.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Split `path` after its first `n` segments into head and rest.
+fn split_segments(path: &str, n: usize) -> Option<(&str, &str)> {
+    match path.match_indices('/').nth(n - 1) {
+        Some((i, _)) => Some((&path[..i], &path[i + 1..])),
+        None => (path.split('/').count() == n).then_some((path, "")),
+    }
+}
+
+/// If `path` lies under the folded `subpath`, the remainder of `path`.
+/// `folded_path` is `path` folded like the subpath; folding never adds or
+/// drops a `/`, so segments line up between the two.
+fn strip_folded<'a>(subpath: &str, path: &'a str, folded_path: &str) -> Option<&'a str> {
+    if subpath.is_empty() {
+        return Some(path);
+    }
+    let n = subpath.split('/').count();
+    let (head, _) = split_segments(folded_path, n)?;
+    let (_, rest) = split_segments(path, n)?;
+    (head == subpath).then(|| rest.trim_start_matches('/'))
+}
+
+/// Read and parse the mapping file at `path`, wrapping any failure in
+/// [`UncPathError::ConfigError`] so the message names the file.
+fn read_config(path: &Path, allow_comments: bool) -> Result<MappingConfig> {
+    fs::read_to_string(path)
+        .map_err(UncPathError::from)
+        .and_then(|content| config::parse_config_with(path, &content, allow_comments))
+        .map_err(|source| config_error(path, source))
+}
+
+/// Write `config` to `path` as pretty-printed JSON through a temporary
+/// file that is renamed over it.
+fn write_config(path: &Path, config: &MappingConfig) -> Result<()> {
+    let json = serde_json::to_string_pretty(config)?;
+
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp = path.with_file_name(tmp_name);
+
+    let written = fs::File::create(&tmp).and_then(|mut file| {
+        file.write_all(json.as_bytes())?;
+        file.write_all(b"\n")?;
+        file.sync_all()?;
+        fs::rename(&tmp, path)
+    });
+    if written.is_err() {
+        // Best effort: the original error is the one worth reporting.
+        let _ = fs::remove_file(&tmp);
+    }
+    Ok(written?)
+}
+
+fn config_error(path: &Path, source: UncPathError) -> UncPathError {
+    UncPathError::ConfigError {
+        path: path.to_path_buf(),
+        source: Box::new(source),
+    }
+}
+
+/// Collect mappings into a table like
+/// [`MappingTable::try_from_iter`], failing on the first mapping
+/// [`add`](MappingTable::add) rejects instead of panicking.
+///
+/// ```
+/// use uncpath::{MappingTable, MountMapping, Result};
+///
+/// let mappings = vec![
+///     MountMapping::new("fs", "team", "/mnt/team"),
+///     MountMapping::new("nas", "data", "/mnt/nas"),
+/// ];
+/// let table: MappingTable = mappings.into_iter().collect::<Result<_>>()?;
+/// assert_eq!(table.len(), 2);
+///
+/// let bad: Result<MappingTable> = [MountMapping::new("h", "s", "rel")].into_iter().collect();
+/// assert!(bad.is_err());
+/// # Ok::<(), uncpath::UncPathError>(())
+/// ```
+impl FromIterator<MountMapping> for Result<MappingTable> {
+    fn from_iter<I: IntoIterator<Item = MountMapping>>(mappings: I) -> Self {
+        MappingTable::try_from_iter(mappings)
+    }
+}
+
+impl MappingTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Table with default settings holding `mappings`, added in order
+    /// through [`add`](Self::add). Fails on the first mapping `add`
+    /// rejects, e.g. one with a relative mount point. Collecting into a
+    /// `Result<MappingTable>` does the same.
+    ///
+    /// ```
+    /// use uncpath::{MappingTable, MountMapping};
+    ///
+    /// let table = MappingTable::try_from_iter(
+    ///     (1..=3).map(|i| MountMapping::new("fs", &format!("team{}", i), &format!("/mnt/{}", i))),
+    /// )?;
+    /// assert_eq!(table.find_mount_point("FS", "team2"), Some("/mnt/2"));
+    ///
+    /// assert!(MappingTable::try_from_iter([MountMapping::new("h", "s", "rel")]).is_err());
+    /// # Ok::<(), uncpath::UncPathError>(())
+    /// ```
+    pub fn try_from_iter(mappings: impl IntoIterator<Item = MountMapping>) -> Result<Self> {
+        let mut table = Self::new();
+        table.try_extend(mappings)?;
+        Ok(table)
+    }
+
+    /// Add each of `mappings` in turn through [`add`](Self::add), stopping
+    /// at the first one it rejects. Mappings before it stay added.
+    pub fn try_extend(&mut self, mappings: impl IntoIterator<Item = MountMapping>) -> Result<()> {
+        for mapping in mappings {
+            self.add(mapping)?;
+        }
+        Ok(())
+    }
+
+    /// Table seeded with the default mappings: those in the file named by
+    /// [`ENV_DEFAULTS`] if it is set and loads, otherwise the built-in
+    /// examples `\\server\shared` -> `/mnt/shared` and `\\nas\data` ->
+    /// `/mnt/nas` (compiled in from `src/defaults.json`). A defaults file
+    /// that fails to load is ignored; use
+    /// [`try_with_defaults`](Self::try_with_defaults) to see the error.
+    pub fn with_defaults() -> Self {
+        Self::try_with_defaults().unwrap_or_else(|_| Self::builtin_defaults())
+    }
+
+    /// Like [`with_defaults`](Self::with_defaults), but failing when the
+    /// file named by [`ENV_DEFAULTS`] does not load instead of falling
+    /// back to the built-in examples.
+    pub fn try_with_defaults() -> Result<Self> {
+        Self::defaults_named_by(std::env::var_os(ENV_DEFAULTS))
+    }
+
+    /// Defaults from the file `path` names, the built-in ones when it is
+    /// unset or empty.
+    fn defaults_named_by(path: Option<OsString>) -> Result<Self> {
+        match path.filter(|path| !path.is_empty()) {
+            Some(path) => Self::with_defaults_from(Path::new(&path)),
+            None => Ok(Self::builtin_defaults()),
+        }
+    }
+
+    /// Table seeded with the mappings in `path` (any format
+    /// [`load_from_file`](Self::load_from_file) reads), marked as defaults.
+    pub fn with_defaults_from(path: &Path) -> Result<Self> {
+        let mut table = Self::new();
+        for mapping in read_config(path, false)?.into_mappings(None)? {
+            table.add(mapping.with_source(MappingSource::Default))?;
+        }
+        Ok(table)
+    }
+
+    fn builtin_defaults() -> Self {
+        let config: MappingConfig =
+            serde_json::from_str(BUILTIN_DEFAULTS).expect("built-in defaults are valid");
+        let mut table = Self::new();
+        for mapping in config.mappings {
+            table.push(mapping.with_source(MappingSource::Default), None);
+        }
+        table
+    }
+
+    /// Empty table that resolves relative mount points against `base_dir`.
+    pub fn with_base_dir(base_dir: impl Into<PathBuf>) -> Self {
+        let mut table = Self::new();
+        table.set_base_dir(Some(base_dir.into()));
+        table
+    }
+
+    pub fn base_dir(&self) -> Option<&Path> {
+        self.base_dir.as_deref()
+    }
+
+    /// Resolve relative mount points against `base_dir` on conversion.
+    /// With a base set, relative mount points are accepted by
+    /// [`add`](Self::add).
+    pub fn set_base_dir(&mut self, base_dir: Option<PathBuf>) {
+        self.base_dir = base_dir;
+    }
+
+    /// `mount_point` joined onto the base directory if it is relative and a
+    /// base is set; otherwise unchanged.
+    pub fn join_base<'a>(&self, mount_point: &'a str) -> Cow<'a, str> {
+        match &self.base_dir {
+            Some(base) if !mount_point.starts_with('/') => {
+                let base = base.to_string_lossy();
+                let mount_point = mount_point.trim_start_matches("./");
+                if mount_point.is_empty() || mount_point == "." {
+                    Cow::Owned(base.into_owned())
+                } else {
+                    Cow::Owned(format!("{}/{}", base.trim_end_matches('/'), mount_point))
+                }
+            }
+            _ => Cow::Borrowed(mount_point),
+        }
+    }
+
+    pub fn admin_share_root(&self) -> Option<&Path> {
+        self.admin_share_root.as_deref()
+    }
+
+    /// Map administrative shares without an explicit mapping below `root`:
+    /// `\\host\C$` becomes `<root>/host/c`, `ADMIN$` the Windows directory
+    /// on drive C. `IPC$` is rejected since it holds no files.
+    pub fn set_admin_share_root(&mut self, root: Option<PathBuf>) {
+        self.admin_share_root = root;
+    }
+
+    pub fn allow_comments(&self) -> bool {
+        self.allow_comments
+    }
+
+    /// Read `.json` config files (and files without a known extension) as
+    /// JSON5, accepting comments and trailing commas. `.json5` files are
+    /// always read that way.
+    pub fn set_allow_comments(&mut self, allow: bool) {
+        self.allow_comments = allow;
+    }
+
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
+    /// Load `profile` instead of [`DEFAULT_PROFILE`](config::DEFAULT_PROFILE)
+    /// from config files that define
+    /// [`profiles`](MappingConfig::profiles). Set it before loading; files
+    /// without profiles are loaded in full either way.
+    pub fn set_profile(&mut self, profile: Option<String>) {
+        self.profile = profile;
+    }
+
+    pub fn override_policy(&self) -> OverridePolicy {
+        self.override_policy
+    }
+
+    /// Choose what [`add`](Self::add) does when a mapping would shadow an
+    /// existing one with a different mount point. Exact duplicates are
+    /// always fine.
+    pub fn set_override_policy(&mut self, policy: OverridePolicy) {
+        self.override_policy = policy;
+    }
+
+    /// The overrides recorded under [`OverridePolicy::Warn`] since the
+    /// last call, as `(shadowed, added)` pairs.
+    ///
+    /// ```
+    /// use uncpath::{MappingTable, MountMapping, OverridePolicy};
+    ///
+    /// let mut table = MappingTable::new();
+    /// table.set_override_policy(OverridePolicy::Warn);
+    /// table.add(MountMapping::new("fs", "team", "/mnt/team"))?;
+    /// table.add(MountMapping::new("fs", "team", "/mnt/team"))?;
+    /// table.add(MountMapping::new("FS", "Team", "/srv/team"))?;
+    /// let overrides = table.take_overrides();
+    /// assert_eq!(overrides.len(), 1);
+    /// assert_eq!(overrides[0].1.mount_point, "/srv/team");
+    ///
+    /// table.set_override_policy(OverridePolicy::Deny);
+    /// assert!(table.add(MountMapping::new("fs", "team", "/elsewhere")).is_err());
+    /// assert_eq!(table.len(), 3);
+    /// # Ok::<(), uncpath::UncPathError>(())
+    /// ```
+    pub fn take_overrides(&mut self) -> Vec<(MountMapping, MountMapping)> {
+        std::mem::take(&mut self.overrides)
+    }
+
+    pub fn strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Make conversion fail with [`UncPathError::MountUnavailable`] when
+    /// the resolved mount point does not exist locally. Off by default,
+    /// since paths are often converted for other machines.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    pub fn case_sensitive(&self) -> bool {
+        self.case_sensitive
+    }
+
+    /// Switch between case-insensitive (the default) and case-sensitive
+    /// matching. Existing mappings are re-indexed and regexes recompiled.
+    pub fn set_case_sensitive(&mut self, case_sensitive: bool) {
+        if self.case_sensitive == case_sensitive {
+            return;
+        }
+        self.case_sensitive = case_sensitive;
+        self.reindex();
+    }
+
+    pub fn host_form(&self) -> HostForm {
+        self.host_form
+    }
+
+    /// Compare internationalized host names in punycode (the default) or
+    /// Unicode form. Existing mappings are re-indexed. IDNA conversion
+    /// lowercases, so such hosts match case-insensitively even in a
+    /// case-sensitive table.
+    pub fn set_host_form(&mut self, form: HostForm) {
+        if self.host_form == form {
+            return;
+        }
+        self.host_form = form;
+        self.reindex();
+    }
+
+    pub fn match_short_names(&self) -> bool {
+        self.match_short_names
+    }
+
+    /// Let a host without dots (a NetBIOS-style short name such as
+    /// `FILESERVER`) match mappings whose host is an FQDN starting with
+    /// that label, e.g. `fileserver.corp.example`. Only tried after exact
+    /// and alias matches fail. Off by default.
+    pub fn set_match_short_names(&mut self, enabled: bool) {
+        self.match_short_names = enabled;
+    }
+
+    /// Rebuild the lookup structures after a matching setting changed.
+    fn reindex(&mut self) {
+        let mappings = std::mem::take(&mut self.mappings);
+        self.clear();
+        for mapping in mappings {
+            let compiled = self
+                .compile(&mapping)
+                .expect("regex mappings are validated when first added");
+            self.push(mapping, compiled);
+        }
+    }
+
+    /// Whether `~` and `$VAR` in mount points are expanded on conversion.
+    pub fn expand_mount_points(&self) -> bool {
+        !self.raw_mount_points
+    }
+
+    /// Turn expansion of `~` and `$VAR` in mount points on (the default)
+    /// or off.
+    pub fn set_expand_mount_points(&mut self, expand: bool) {
+        self.raw_mount_points = !expand;
+    }
+
+    pub fn trim_components(&self) -> bool {
+        self.trim_components
+    }
+
+    /// Strip leading and trailing whitespace from each segment of the path
+    /// below the share before converting, for paths pasted from documents
+    /// (`My Folder \file`). Off by default, since such names are legal.
+    /// The host and share are left alone.
+    ///
+    /// ```
+    /// use uncpath::{convert_to_posix, MappingTable};
+    ///
+    /// let mut table = MappingTable::new();
+    /// table.add_mapping("server", "shared", "/mnt/shared")?;
+    /// let input = r"\\server\shared\ My Folder \file.txt ";
+    /// assert_eq!(convert_to_posix(input, &table)?, "/mnt/shared/ My Folder /file.txt");
+    /// table.set_trim_components(true);
+    /// assert_eq!(convert_to_posix(input, &table)?, "/mnt/shared/My Folder/file.txt");
+    /// # Ok::<(), uncpath::UncPathError>(())
+    /// ```
+    pub fn set_trim_components(&mut self, trim: bool) {
+        self.trim_components = trim;
+    }
+
+    pub fn lowercase_output(&self) -> bool {
+        self.lowercase_output
+    }
+
+    /// Lowercase the entire converted POSIX path, mount point included,
+    /// for case-insensitive target file systems (CIFS mounts, `ciopfs`).
+    /// This is unrelated to [`case_sensitive`](Self::case_sensitive)
+    /// lookups. Off by default: on a case-sensitive Linux file system the
+    /// lowercased path usually does not exist.
+    ///
+    /// ```
+    /// use uncpath::{convert_to_posix, MappingTable};
+    ///
+    /// let mut table = MappingTable::new();
+    /// table.add_mapping("server", "shared", "/mnt/Shared")?;
+    /// table.set_lowercase_output(true);
+    /// let posix = convert_to_posix(r"\\SERVER\Shared\Projects\ReadMe.TXT", &table)?;
+    /// assert_eq!(posix, "/mnt/shared/projects/readme.txt");
+    /// # Ok::<(), uncpath::UncPathError>(())
+    /// ```
+    pub fn set_lowercase_output(&mut self, lowercase: bool) {
+        self.lowercase_output = lowercase;
+    }
+
+    /// Whether conversions read `//host/share` inputs as UNC paths.
+    pub fn unix_style_unc(&self) -> bool {
+        !self.no_unix_style_unc
+    }
+
+    /// Read `//host/share` inputs as UNC paths (the default), or reject
+    /// them, for setups where `//srv/data` is a local path. `smb://`,
+    /// `file://` and `nfs://` URLs are unaffected. See
+    /// [`UncParser::set_unix_style`](crate::UncParser::set_unix_style).
+    ///
+    /// ```
+    /// use uncpath::{convert_to_posix, MappingTable};
+    ///
+    /// let mut table = MappingTable::new();
+    /// table.add_mapping("srv", "data", "/mnt/data")?;
+    /// assert_eq!(convert_to_posix("//srv/data/x", &table)?, "/mnt/data/x");
+    ///
+    /// table.set_unix_style_unc(false);
+    /// assert!(convert_to_posix("//srv/data/x", &table).is_err());
+    /// assert_eq!(convert_to_posix("smb://srv/data/x", &table)?, "/mnt/data/x");
+    /// # Ok::<(), uncpath::UncPathError>(())
+    /// ```
+    pub fn set_unix_style_unc(&mut self, enabled: bool) {
+        self.no_unix_style_unc = !enabled;
+    }
+
+    /// The parser conversions read their input with.
+    pub fn parser(&self) -> &UncParser {
+        self.parser.as_ref().unwrap_or(&DEFAULT_PARSER)
+    }
+
+    /// Read conversion input with `parser` instead of the built-in one, so
+    /// schemes registered on it reach [`convert_to_posix`](crate::convert_to_posix)
+    /// and the other table-based conversions. Whether `//host/share` is
+    /// accepted is still decided by [`set_unix_style_unc`](Self::set_unix_style_unc).
+    ///
+    /// ```
+    /// use uncpath::{convert_to_posix, MappingTable, UncParser, UncPath};
+    ///
+    /// let mut parser = UncParser::new();
+    /// parser.register_scheme("dav", |input| {
+    ///     let rest = &input["dav://".len()..];
+    ///     let (host, share) = rest.split_once('/').unwrap_or((rest, ""));
+    ///     Ok(UncPath { host: host.into(), share: share.into(), ..Default::default() })
+    /// });
+    ///
+    /// let mut table = MappingTable::new();
+    /// table.add_mapping("web", "site", "/mnt/site")?;
+    /// assert!(convert_to_posix("dav://web/site", &table).is_err());
+    /// table.set_parser(parser);
+    /// assert_eq!(convert_to_posix("dav://web/site", &table)?, "/mnt/site");
+    /// # Ok::<(), uncpath::UncPathError>(())
+    /// ```
+    pub fn set_parser(&mut self, parser: UncParser) {
+        self.parser = Some(parser);
+    }
+
+    /// Whether mount points may be relative paths.
+    pub fn allow_relative(&self) -> bool {
+        self.allow_relative
+    }
+
+    /// Accept (or, by default, reject) mount points that are not absolute.
+    /// Only affects mappings added afterwards.
+    pub fn set_allow_relative(&mut self, allow: bool) {
+        self.allow_relative = allow;
+    }
+
+    fn key(&self, host: &str, share: &str) -> (String, String) {
+        (
+            self.fold_host(host),
+            fold(share, self.case_sensitive).into_owned(),
+        )
+    }
+
+    /// `host` as lookups compare it: in the table's [`HostForm`], then
+    /// case-folded.
+    fn fold_host(&self, host: &str) -> String {
+        fold(&self.host_form.normalize(host), self.case_sensitive).into_owned()
+    }
+
+    /// Add a literal or wildcard mapping. Fails if the mount point is not
+    /// absolute, see [`add`](Self::add).
+    pub fn add_mapping(&mut self, host: &str, share: &str, mount_point: &str) -> Result<()> {
+        self.add(MountMapping::new(host, share, mount_point))
+    }
+
+    /// Add a prepared mapping. Fails if a regex mapping does not compile or
+    /// the mount point is relative and the table does not allow that. Mount
+    /// points starting with `~` or `$` count as absolute since they are
+    /// expanded on conversion.
+    pub fn add(&mut self, mapping: MountMapping) -> Result<()> {
+        let relative_ok = self.allow_relative || self.base_dir.is_some();
+        if !relative_ok && !is_absolute_mount(&mapping.mount_point) {
+            return Err(UncPathError::InvalidMapping(format!(
+                "mount point '{}' for {} is not an absolute path",
+                mapping.mount_point,
+                mapping.unc_prefix()
+            )));
+        }
+        let compiled = self.compile(&mapping)?;
+        if self.override_policy != OverridePolicy::Allow {
+            self.check_override(&mapping)?;
+        }
+        self.push(mapping, compiled);
+        Ok(())
+    }
+
+    /// Apply the override policy to `mapping`, which is about to be added.
+    fn check_override(&mut self, mapping: &MountMapping) -> Result<()> {
+        let key = self.folded_key(mapping);
+        let Some(shadowed) = self
+            .mappings
+            .iter()
+            .zip(&self.keys)
+            .rev()
+            .find(|(m, k)| **k == key && m.regex == mapping.regex)
+            .map(|(m, _)| m)
+            .filter(|m| m.mount_point != mapping.mount_point)
+        else {
+            return Ok(());
+        };
+        if self.override_policy == OverridePolicy::Deny {
+            return Err(UncPathError::ConflictingMappings(format!(
+                "{} -> {} ({}) would override {} ({})",
+                mapping.unc_prefix(),
+                mapping.mount_point,
+                mapping.source(),
+                shadowed.mount_point,
+                shadowed.source()
+            )));
+        }
+        self.overrides.push((shadowed.clone(), mapping.clone()));
+        Ok(())
+    }
+
+    fn compile(&self, mapping: &MountMapping) -> Result<Option<(Regex, Regex)>> {
+        if !mapping.regex {
+            return Ok(None);
+        }
+        Ok(Some((
+            compile_pattern(&mapping.host, self.case_sensitive)?,
+            compile_pattern(&mapping.share, self.case_sensitive)?,
+        )))
+    }
+
+    fn push(&mut self, mut mapping: MountMapping, compiled: Option<(Regex, Regex)>) {
+        mapping.normalize_subpath();
+        let position = self.mappings.len();
+        let key = self.folded_key(&mapping);
+        if let Some((host, share)) = compiled {
+            self.patterns.push((position, host, share));
+        } else if mapping.is_wildcard() {
+            self.wildcards.push(position);
+        } else {
+            let index_key = (key.host.clone(), key.share.clone());
+            self.index
+                .entry(index_key)
+                .or_default()
+                .push(position, &key.subpath);
+            for alias in &mapping.aliases {
+                let alias_key = (self.fold_host(alias), key.share.clone());
+                self.alias_index
+                    .entry(alias_key)
+                    .or_default()
+                    .push(position, &key.subpath);
+            }
+            if let Some(label) = short_name(&key.host) {
+                let short_key = (label.to_string(), key.share.clone());
+                self.short_index
+                    .entry(short_key)
+                    .or_default()
+                    .push(position, &key.subpath);
+            }
+        }
+        self.keys.push(key);
+        self.mappings.push(mapping);
+    }
+
+    /// Remove every mapping for `host`/`share`, compared like lookups.
+    /// `share` may include a subpath to remove only that mapping. Returns
+    /// whether anything was removed.
+    pub fn remove_mapping(&mut self, host: &str, share: &str) -> bool {
+        let target = MountMapping::new(host, share, "");
+        let target = self.folded_key(&target);
+        self.retain(|m, key| m.regex || *key != target) > 0
+    }
+
+    /// Remove and return the mapping at `index` in
+    /// [`get_mappings`](Self::get_mappings) order, leaving any other
+    /// mapping for the same host/share in place. `None` if out of range.
+    pub fn remove_at(&mut self, index: usize) -> Option<MountMapping> {
+        let removed = self.mappings.get(index)?.clone();
+        let kept = (0..self.mappings.len()).filter(|&i| i != index).collect();
+        self.rebuild(kept);
+        Some(removed)
+    }
+
+    /// Drop every mapping that does not carry `tag`, untagged ones
+    /// included. Returns the number of mappings dropped.
+    pub fn retain_tagged(&mut self, tag: &str) -> usize {
+        self.retain(|m, _| m.has_tag(tag))
+    }
+
+    /// Drop every mapping not loaded from `source`, e.g. to save only what
+    /// came from one config file. Returns the number of mappings dropped.
+    pub fn retain_source(&mut self, source: &MappingSource) -> usize {
+        self.retain(|m, _| m.source() == source)
+    }
+
+    /// Identity of a mapping for lookups, removal and conflict detection.
+    fn folded_key(&self, m: &MountMapping) -> FoldedKey {
+        let (host, share) = self.key(&m.host, &m.share);
+        let subpath = fold(&m.subpath, self.case_sensitive).into_owned();
+        FoldedKey {
+            host,
+            share,
+            subpath,
+        }
+    }
+
+    /// Remove all mappings.
+    pub fn clear(&mut self) {
+        self.mappings.clear();
+        self.keys.clear();
+        self.index.clear();
+        self.alias_index.clear();
+        self.short_index.clear();
+        self.wildcards.clear();
+        self.patterns.clear();
+    }
+
+    /// Pairs of mappings for the same host/share/subpath that disagree on
+    /// the mount point, as `(shadowed, winner)`. Exact duplicates are fine.
+    pub fn conflicts(&self) -> Vec<(&MountMapping, &MountMapping)> {
+        let mut seen: HashMap<_, &MountMapping> = HashMap::new();
+        let mut conflicts = Vec::new();
+        for (mapping, key) in self.mappings.iter().zip(&self.keys) {
+            if let Some(previous) = seen.insert((key, mapping.regex), mapping) {
+                if previous.mount_point != mapping.mount_point {
+                    conflicts.push((previous, mapping));
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// Fail with [`UncPathError::ConflictingMappings`] if any host/share is
+    /// mapped to more than one mount point.
+    pub fn validate(&self) -> Result<()> {
+        let conflicts = self.conflicts();
+        if conflicts.is_empty() {
+            return Ok(());
+        }
+
+        let details = conflicts
+            .iter()
+            .map(|(shadowed, winner)| {
+                format!(
+                    "{} -> {} is overridden by {}",
+                    shadowed.unc_prefix(),
+                    shadowed.mount_point,
+                    winner.mount_point
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("; ");
+        Err(UncPathError::ConflictingMappings(details))
+    }
+
+    /// Keep only mappings for which `keep` returns true, rebuilding the
+    /// lookup structures. Returns the number of mappings dropped.
+    fn retain(&mut self, mut keep: impl FnMut(&MountMapping, &FoldedKey) -> bool) -> usize {
+        let before = self.mappings.len();
+        let kept: Vec<usize> = (0..before)
+            .filter(|&i| keep(&self.mappings[i], &self.keys[i]))
+            .collect();
+        self.rebuild(kept);
+        before - self.mappings.len()
+    }
+
+    /// Rebuild the table from the mappings at `positions`, in that order,
+    /// reusing compiled patterns. Positions left out are dropped.
+    fn rebuild(&mut self, positions: Vec<usize>) {
+        let mut compiled: HashMap<usize, (Regex, Regex)> = std::mem::take(&mut self.patterns)
+            .into_iter()
+            .map(|(i, host, share)| (i, (host, share)))
+            .collect();
+        let mut old: Vec<Option<MountMapping>> = std::mem::take(&mut self.mappings)
+            .into_iter()
+            .map(Some)
+            .collect();
+        self.clear();
+
+        for i in positions {
+            if let Some(mapping) = old[i].take() {
+                let patterns = compiled.remove(&i);
+                self.push(mapping, patterns);
+            }
+        }
+    }
+
+    /// Add the mappings of `other` after this table's, resolving mappings
+    /// for the same host/share/subpath (compared like lookups, so by this
+    /// table's settings) according to `policy`. `other`'s settings and
+    /// drive mappings are ignored. Fails, leaving the table partly merged,
+    /// on a mapping [`add`](Self::add) rejects.
+    ///
+    /// The CLI assembles its table by appending defaults, the config file,
+    /// the environment, `--file` and `--mapping` in that order, so each
+    /// shadows the ones before. [`MergePolicy::Overwrite`] resolves
+    /// lookups the same way but leaves no shadowed mappings behind;
+    /// [`MergePolicy::KeepExisting`] gives the earlier source precedence
+    /// instead.
+    ///
+    /// ```
+    /// use uncpath::{MappingTable, MergePolicy, MountMapping};
+    ///
+    /// let base = MappingTable::try_from_iter([
+    ///     MountMapping::new("fs", "team", "/mnt/old"),
+    ///     MountMapping::new("fs", "home", "/home"),
+    /// ])?;
+    /// let update = MappingTable::try_from_iter([
+    ///     MountMapping::new("FS", "team", "/mnt/new"),
+    ///     MountMapping::new("fs", "data", "/data"),
+    /// ])?;
+    /// let mounts = |policy| -> uncpath::Result<(usize, Option<String>)> {
+    ///     let mut table = base.clone();
+    ///     table.merge(update.clone(), policy)?;
+    ///     Ok((table.len(), table.find_mount_point("fs", "team").map(String::from)))
+    /// };
+    ///
+    /// assert_eq!(mounts(MergePolicy::KeepExisting)?, (3, Some("/mnt/old".into())));
+    /// assert_eq!(mounts(MergePolicy::Overwrite)?, (3, Some("/mnt/new".into())));
+    /// assert_eq!(mounts(MergePolicy::Append)?, (4, Some("/mnt/new".into())));
+    /// # Ok::<(), uncpath::UncPathError>(())
+    /// ```
+    pub fn merge(&mut self, other: MappingTable, policy: MergePolicy) -> Result<()> {
+        let incoming: HashSet<(FoldedKey, bool)> = other
+            .mappings
+            .iter()
+            .map(|m| (self.folded_key(m), m.regex))
+            .collect();
+        let existing: HashSet<(FoldedKey, bool)> = self
+            .mappings
+            .iter()
+            .zip(&self.keys)
+            .map(|(m, key)| (key.clone(), m.regex))
+            .collect();
+
+        if policy == MergePolicy::Overwrite {
+            self.retain(|m, key| !incoming.contains(&(key.clone(), m.regex)));
+        }
+        for mapping in other.mappings {
+            let key = (self.folded_key(&mapping), mapping.regex);
+            if policy == MergePolicy::KeepExisting && existing.contains(&key) {
+                continue;
+            }
+            self.add(mapping)?;
+        }
+        Ok(())
+    }
+
+    /// Order the mappings by host, then share (and subpath), then mount
+    /// point. The sort is stable, so equal mappings keep their order.
+    ///
+    /// Since later mappings win, sorting decides which of two conflicting
+    /// mappings for the same share takes effect; see
+    /// [`conflicts`](Self::conflicts).
+    pub fn sort(&mut self) {
+        let mut order: Vec<usize> = (0..self.mappings.len()).collect();
+        order.sort_by(|&a, &b| {
+            let (a, b) = (&self.mappings[a], &self.mappings[b]);
+            (&a.host, &a.share, &a.subpath, &a.mount_point).cmp(&(
+                &b.host,
+                &b.share,
+                &b.subpath,
+                &b.mount_point,
+            ))
+        });
+        self.rebuild(order);
+    }
+
+    /// Remove mappings identical to an earlier one in everything but
+    /// their [`source`](MountMapping::source), keeping the first. Returns
+    /// the number removed.
+    ///
+    /// ```
+    /// use uncpath::{MappingTable, MountMapping};
+    ///
+    /// let mut table = MappingTable::new();
+    /// for (host, mount) in [("b", "/mnt/b"), ("a", "/mnt/a"), ("b", "/mnt/b")] {
+    ///     table.add(MountMapping::new(host, "s", mount))?;
+    /// }
+    /// assert_eq!(table.dedupe(), 1);
+    /// table.sort();
+    /// let hosts: Vec<&str> = table.iter().map(|m| m.host.as_str()).collect();
+    /// assert_eq!(hosts, ["a", "b"]);
+    /// # Ok::<(), uncpath::UncPathError>(())
+    /// ```
+    pub fn dedupe(&mut self) -> usize {
+        let mut seen = HashSet::new();
+        self.retain(|m, _| {
+            seen.insert(MountMapping {
+                source: MappingSource::default(),
+                ..m.clone()
+            })
+        })
+    }
+
+    /// Literal mappings for `host`/`share`: by primary host name, failing
+    /// that by alias, failing that (if enabled) by short name.
+    fn exact_bucket(&self, host: &str, share: &str) -> Option<&Bucket> {
+        type Index = HashMap<(String, String), Bucket>;
+        fn lookup<'i>(index: &'i Index, key: &(String, String)) -> Option<&'i Bucket> {
+            index.get(key).filter(|bucket| !bucket.positions.is_empty())
+        }
+
+        let key = self.key(host, share);
+        let short = self.match_short_names && !key.0.contains('.');
+        lookup(&self.index, &key)
+            .or_else(|| lookup(&self.alias_index, &key))
+            .or_else(|| short.then(|| lookup(&self.short_index, &key)).flatten())
+    }
+
+    fn exact_candidates(&self, host: &str, share: &str) -> impl Iterator<Item = usize> + '_ {
+        self.exact_bucket(host, share)
+            .into_iter()
+            .flat_map(|bucket| bucket.positions.iter().copied())
+    }
+
+    /// Like [`longest_match`](Self::longest_match) over the literal
+    /// mappings for `host`/`share`, walking their subpath trie when there
+    /// are subpath mappings.
+    fn longest_exact_match<'a>(
+        &self,
+        host: &str,
+        share: &str,
+        path: &'a str,
+        folded_path: &str,
+    ) -> Option<(&MountMapping, &'a str)> {
+        let bucket = self.exact_bucket(host, share)?;
+        if !bucket.subpaths.has_subpaths() {
+            let i = *bucket.positions.last()?;
+            return Some((&self.mappings[i], path));
+        }
+        let (i, depth) = bucket.subpaths.deepest(folded_path)?;
+        let rest = match depth {
+            0 => path,
+            n => split_segments(path, n).map_or("", |(_, rest)| rest.trim_start_matches('/')),
+        };
+        Some((&self.mappings[i], rest))
+    }
+
+    fn wildcard_candidates(&self, host: &str, share: &str) -> impl Iterator<Item = usize> + '_ {
+        let (host, share) = self.key(host, share);
+        self.wildcards.iter().copied().filter(move |&i| {
+            let key = &self.keys[i];
+            glob_match(&key.host, &host) && glob_match(&key.share, &share)
+        })
+    }
+
+    fn regex_candidates<'s: 'q, 'q>(
+        &'s self,
+        host: &'q str,
+        share: &'q str,
+    ) -> impl Iterator<Item = usize> + 'q {
+        self.patterns
+            .iter()
+            .filter(move |(_, h, s)| h.is_match(host) && s.is_match(share))
+            .map(|(i, _, _)| *i)
+    }
+
+    /// The candidate with the longest subpath prefixing `path`; ties go to
+    /// the last one.
+    fn longest_match<'a>(
+        &self,
+        candidates: impl Iterator<Item = usize>,
+        path: &'a str,
+        folded_path: &str,
+    ) -> Option<(&MountMapping, &'a str)> {
+        candidates
+            .filter_map(|i| {
+                let rest = strip_folded(&self.keys[i].subpath, path, folded_path)?;
+                Some((&self.mappings[i], rest))
+            })
+            .max_by_key(|(m, _)| m.subpath.len())
+    }
+
+    /// Capture groups of a regex `mapping` matched against `host`/`share`,
+    /// numbered from 1: host groups first, then share groups. Groups that
+    /// did not participate are empty. `None` for non-regex mappings.
+    pub fn regex_captures(
+        &self,
+        mapping: &MountMapping,
+        host: &str,
+        share: &str,
+    ) -> Option<Vec<String>> {
+        let (_, host_re, share_re) = self
+            .patterns
+            .iter()
+            .find(|(i, _, _)| std::ptr::eq(&self.mappings[*i], mapping))?;
+
+        let mut groups = Vec::new();
+        for (re, text) in [(host_re, host), (share_re, share)] {
+            let caps = re.captures(text)?;
+            groups.extend(
+                caps.iter()
+                    .skip(1)
+                    .map(|m| m.map_or(String::new(), |m| m.as_str().to_string())),
+            );
+        }
+        Some(groups)
+    }
+
+    /// Every mapping whose host matches `host`, in insertion order. Hosts
+    /// compare like lookups do: case-insensitively unless the table is
+    /// case-sensitive, with globs and regexes applied to the host alone.
+    pub fn find_all_for_host(&self, host: &str) -> Vec<&MountMapping> {
+        let folded = self.fold_host(host);
+        self.mappings
+            .iter()
+            .zip(&self.keys)
+            .enumerate()
+            .filter(|(i, (m, key))| {
+                if m.regex {
+                    self.patterns
+                        .iter()
+                        .any(|(p, pattern, _)| p == i && pattern.is_match(host))
+                } else if m.host.contains('*') {
+                    glob_match(&key.host, &folded)
+                } else {
+                    key.host == folded || m.aliases.iter().any(|a| self.fold_host(a) == folded)
+                }
+            })
+            .map(|(_, (m, _))| m)
+            .collect()
+    }
+
+    /// The literal mapping closest to `host`/`share`, for "did you mean"
+    /// hints after a failed lookup. Distance is the edit distance of the
+    /// host plus that of the share; nothing further than
+    /// [`SUGGEST_DISTANCE`] is suggested. Ties go to the first mapping.
+    pub fn suggest(&self, host: &str, share: &str) -> Option<&MountMapping> {
+        let (host, share) = self.key(host, share);
+        self.mappings
+            .iter()
+            .zip(&self.keys)
+            .filter(|(m, _)| !m.regex && !m.is_wildcard())
+            .map(|(m, key)| {
+                let distance = levenshtein(&host, &key.host) + levenshtein(&share, &key.share);
+                (distance, m)
+            })
+            .filter(|&(distance, _)| distance <= SUGGEST_DISTANCE)
+            .min_by_key(|&(distance, _)| distance)
+            .map(|(_, m)| m)
+    }
+
+    /// Look up the mount point for a whole `host`/`share`.
+    /// Exact mappings win over regex mappings, which win over wildcards.
+    pub fn find_mount_point(&self, host: &str, share: &str) -> Option<&str> {
+        self.get(host, share).map(|m| m.mount_point.as_str())
+    }
+
+    /// The mapping [`find_mount_point`](Self::find_mount_point) takes its
+    /// mount point from, with its description, tags and source.
+    ///
+    /// ```
+    /// use uncpath::{MappingSource, MappingTable, MountMapping};
+    ///
+    /// let mut table = MappingTable::new();
+    /// table.add(
+    ///     MountMapping::new("Server", "Projects", "/mnt/projects")
+    ///         .with_description("Team projects")
+    ///         .with_tags(["team", "nightly"])
+    ///         .with_source(MappingSource::Cli),
+    /// )?;
+    ///
+    /// let m = table.get("SERVER", "projects").unwrap();
+    /// assert_eq!(m.mount_point, "/mnt/projects");
+    /// assert_eq!(m.description.as_deref(), Some("Team projects"));
+    /// assert_eq!(m.tags, ["team", "nightly"]);
+    /// assert_eq!(*m.source(), MappingSource::Cli);
+    /// assert!(table.get("server", "other").is_none());
+    /// # Ok::<(), uncpath::UncPathError>(())
+    /// ```
+    pub fn get(&self, host: &str, share: &str) -> Option<&MountMapping> {
+        let whole_share = |&i: &usize| self.mappings[i].subpath.is_empty();
+        self.exact_bucket(host, share)
+            .and_then(|bucket| bucket.subpaths.positions.last().copied())
+            .or_else(|| {
+                self.regex_candidates(host, share)
+                    .filter(whole_share)
+                    .last()
+            })
+            .or_else(|| {
+                self.wildcard_candidates(host, share)
+                    .filter(whole_share)
+                    .last()
+            })
+            .map(|i| &self.mappings[i])
+    }
+
+    /// Every mapping that could match `host`/`share`, best first: exact
+    /// mappings (by primary name, else alias, else short name), then regex
+    /// and then wildcard mappings, each group latest first. Mappings for a
+    /// subpath are included, since they match depending on the path. The
+    /// first whole-share entry is what
+    /// [`find_mount_point`](Self::find_mount_point) returns.
+    pub fn find_all_mount_points(&self, host: &str, share: &str) -> Vec<&MountMapping> {
+        let exact: Vec<usize> = self.exact_candidates(host, share).collect();
+        let regex: Vec<usize> = self.regex_candidates(host, share).collect();
+        let wildcard: Vec<usize> = self.wildcard_candidates(host, share).collect();
+        [exact, regex, wildcard]
+            .into_iter()
+            .flat_map(|positions| positions.into_iter().rev())
+            .map(|i| &self.mappings[i])
+            .collect()
+    }
+
+    /// Find the mapping whose `host/share/subpath` is the longest prefix of
+    /// `host/share/path`, returning it with the remainder of `path`.
+    /// Among equally specific mappings the one added last wins. Regex and
+    /// then wildcard mappings are only consulted when nothing more exact
+    /// matches.
+    ///
+    /// Literal mappings are found by walking a trie of their subpath
+    /// segments, so the cost grows with the depth of `path` rather than
+    /// with the number of mappings for the share:
+    ///
+    /// ```
+    /// use uncpath::{MappingTable, MountMapping};
+    ///
+    /// let mut table = MappingTable::new();
+    /// table.add(MountMapping::new("server", "share", "/mnt/share"))?;
+    /// table.add(MountMapping::new("server", "share/a/b/c", "/mnt/deep"))?;
+    /// table.add(MountMapping::new("server", "share/a", "/mnt/a"))?;
+    /// table.add(MountMapping::new("server", "share/A/B", "/mnt/ab"))?;
+    ///
+    /// let find = |path| {
+    ///     let (m, rest) = table.find_longest_match("server", "share", path).unwrap();
+    ///     (m.mount_point.as_str(), rest)
+    /// };
+    /// assert_eq!(find("a/b/c/d/e.txt"), ("/mnt/deep", "d/e.txt"));
+    /// assert_eq!(find("a/b/x"), ("/mnt/ab", "x"));
+    /// assert_eq!(find("a/bc"), ("/mnt/a", "bc"));
+    /// assert_eq!(find("b/a"), ("/mnt/share", "b/a"));
+    /// # Ok::<(), uncpath::UncPathError>(())
+    /// ```
+    pub fn find_longest_match<'a>(
+        &self,
+        host: &str,
+        share: &str,
+        path: &'a str,
+    ) -> Option<(&MountMapping, &'a str)> {
+        let folded = fold(path, self.case_sensitive);
+        self.longest_exact_match(host, share, path, &folded)
+            .or_else(|| self.longest_match(self.regex_candidates(host, share), path, &folded))
+            .or_else(|| self.longest_match(self.wildcard_candidates(host, share), path, &folded))
+    }
+
+    /// Load mappings from a JSON, JSON5 or YAML file (a list of mappings or
+    /// a versioned [`MappingConfig`]) or a TOML file (`[[mapping]]` tables).
+    /// The format follows the extension; unknown extensions try each in
+    /// turn. See [`set_allow_comments`](Self::set_allow_comments) for
+    /// comments in `.json` files.
+    ///
+    /// Read and parse failures come back as [`UncPathError::ConfigError`],
+    /// naming the file:
+    ///
+    /// ```
+    /// use uncpath::{MappingTable, UncPathError};
+    ///
+    /// let path = std::env::temp_dir().join(format!("uncpath-doc-{}.json", std::process::id()));
+    /// std::fs::write(&path, r#"[{"host": "server", "share": }]"#)?;
+    /// let err = MappingTable::new().load_from_file(&path).unwrap_err();
+    /// std::fs::remove_file(&path)?;
+    ///
+    /// assert!(matches!(&err, UncPathError::ConfigError { source, .. }
+    ///     if matches!(**source, UncPathError::JsonError(_))));
+    /// assert!(err.to_string().starts_with(&format!("failed to load mappings from {}: ", path.display())));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Includes
+    ///
+    /// Files listed in a config's [`include`](MappingConfig::include) are
+    /// loaded first, relative to the including file's directory, and may
+    /// include others in turn up to [`MAX_INCLUDE_DEPTH`] levels. A file
+    /// that includes itself, directly or not, fails with
+    /// [`UncPathError::IncludeCycle`]:
+    ///
+    /// ```
+    /// use uncpath::{MappingTable, UncPathError};
+    ///
+    /// let dir = std::env::temp_dir().join(format!("uncpath-include-{}", std::process::id()));
+    /// std::fs::create_dir_all(dir.join("teams"))?;
+    /// let write = |name: &str, content: &str| std::fs::write(dir.join(name), content);
+    /// write("main.json", r#"{"include": ["teams/dev.json"],
+    ///     "mappings": [{"host": "fs", "share": "main", "mount_point": "/mnt/main"}]}"#)?;
+    /// write("teams/dev.json", r#"{"include": ["../base.json"],
+    ///     "mappings": [{"host": "fs", "share": "dev", "mount_point": "/mnt/dev"}]}"#)?;
+    /// write("base.json", r#"[{"host": "fs", "share": "base", "mount_point": "/mnt/base"}]"#)?;
+    ///
+    /// let mut table = MappingTable::new();
+    /// table.load_from_file(&dir.join("main.json"))?;
+    /// let mounts: Vec<_> = table.iter().map(|m| m.mount_point.as_str()).collect();
+    /// assert_eq!(mounts, ["/mnt/base", "/mnt/dev", "/mnt/main"]);
+    ///
+    /// write("a.json", r#"{"include": ["b.json"]}"#)?;
+    /// write("b.json", r#"{"include": ["a.json"]}"#)?;
+    /// let err = MappingTable::new().load_from_file(&dir.join("a.json")).unwrap_err();
+    /// assert!(matches!(err, UncPathError::IncludeCycle(_)));
+    /// std::fs::remove_dir_all(&dir)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn load_from_file(&mut self, path: &Path) -> Result<()> {
+        self.load_including(path, &mut Vec::new())
+    }
+
+    /// Load `path` after its includes. `chain` holds the canonical paths
+    /// of the files currently being loaded, outermost first.
+    fn load_including(&mut self, path: &Path, chain: &mut Vec<PathBuf>) -> Result<()> {
+        let canonical = fs::canonicalize(path).map_err(|e| config_error(path, e.into()))?;
+        if let Some(start) = chain.iter().position(|p| *p == canonical) {
+            let cycle = chain[start..]
+                .iter()
+                .chain([&canonical])
+                .map(|p| p.display().to_string())
+                .collect::<Vec<_>>()
+                .join(" -> ");
+            return Err(UncPathError::IncludeCycle(cycle));
+        }
+        if chain.len() > MAX_INCLUDE_DEPTH {
+            return Err(UncPathError::IncludeTooDeep(path.to_path_buf()));
+        }
+
+        let mut config = read_config(path, self.allow_comments)?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+        chain.push(canonical);
+        for include in std::mem::take(&mut config.include) {
+            self.load_including(&dir.join(include), chain)?;
+        }
+        chain.pop();
+
+        for mapping in config.into_mappings(self.profile.as_deref())? {
+            self.add(mapping.with_source(MappingSource::File(path.to_path_buf())))?;
+        }
+        Ok(())
+    }
+
+    /// Load every JSON, JSON5, TOML and YAML file directly inside `dir` (a
+    /// `conf.d`-style directory) in filename order. Since later mappings
+    /// win, a file overrides the ones sorting before it; prefixes such as
+    /// `10-base.json` and `90-local.json` make the precedence explicit.
+    /// Subdirectories, hidden files and other extensions are skipped. For
+    /// another precedence, pass [`config_files_in`](Self::config_files_in)
+    /// reordered to [`load_from_files`](Self::load_from_files).
+    pub fn load_from_dir(&mut self, dir: &Path) -> Result<()> {
+        self.load_from_files(Self::config_files_in(dir)?)
+    }
+
+    /// The config files [`load_from_dir`](Self::load_from_dir) would load
+    /// from `dir`, in the order it loads them.
+    pub fn config_files_in(dir: &Path) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        let io_error = |e: std::io::Error| config_error(dir, e.into());
+        for entry in fs::read_dir(dir).map_err(io_error)? {
+            let path = entry.map_err(io_error)?.path();
+            let hidden = path
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+            if path.is_file() && !hidden && ConfigFormat::from_path(&path).is_some() {
+                files.push(path);
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+
+    /// Load `files` in the given order, so each overrides the ones before
+    /// it. Stops at the first file that fails to load.
+    ///
+    /// ```no_run
+    /// use std::path::Path;
+    /// use uncpath::MappingTable;
+    ///
+    /// // Let the first file in name order win instead of the last.
+    /// let mut files = MappingTable::config_files_in(Path::new("/etc/uncpath/conf.d"))?;
+    /// files.reverse();
+    /// let mut table = MappingTable::new();
+    /// table.load_from_files(files)?;
+    /// # Ok::<(), uncpath::UncPathError>(())
+    /// ```
+    pub fn load_from_files<P: AsRef<Path>>(
+        &mut self,
+        files: impl IntoIterator<Item = P>,
+    ) -> Result<()> {
+        for file in files {
+            self.load_from_file(file.as_ref())?;
+        }
+        Ok(())
+    }
+
+    /// Snapshot the mappings as a versioned [`MappingConfig`].
+    pub fn to_config(&self) -> MappingConfig {
+        MappingConfig::new(self.mappings.clone())
+    }
+
+    /// Build a table with default settings from `config`, rejecting schema
+    /// versions this build does not know.
+    pub fn from_config(config: MappingConfig) -> Result<Self> {
+        config.check_version()?;
+        let mut table = Self::new();
+        for mapping in config.into_mappings(None)? {
+            table.add(mapping)?;
+        }
+        Ok(table)
+    }
+
+    /// Write the mappings to `path` as a pretty-printed JSON
+    /// [`MappingConfig`]. The data goes to a temporary file next to `path`
+    /// first and is then renamed over it, so an interrupted write never
+    /// truncates an existing config.
+    pub fn save_to_file(&self, path: &Path) -> Result<()> {
+        write_config(path, &self.to_config())
+    }
+
+    /// Write the mappings loaded from the config file `path` (those whose
+    /// [`source`](MountMapping::source) is that file) back into it, keeping
+    /// the rest of the file: its [`include`](MappingConfig::include)s, the
+    /// mappings of included files and the profiles that were not loaded.
+    /// Shared mappings still present stay shared; every other mapping goes
+    /// to the loaded [`profile`](Self::profile) if the file has profiles.
+    /// The file is rewritten as JSON, like [`save_to_file`](Self::save_to_file).
+    /// Returns the number of mappings written.
+    pub fn write_back(&self, path: &Path) -> Result<usize> {
+        let mut config = if path.exists() {
+            read_config(path, self.allow_comments)?
+        } else {
+            MappingConfig::new(Vec::new())
+        };
+        let source = MappingSource::File(path.to_path_buf());
+        let mut mappings: Vec<MountMapping> = self
+            .mappings
+            .iter()
+            .filter(|m| *m.source() == source)
+            .map(|m| m.clone().with_source(MappingSource::default()))
+            .collect();
+        let written = mappings.len();
+
+        if config.profiles.is_empty() {
+            config.mappings = mappings;
+        } else {
+            let shared = std::mem::take(&mut config.mappings);
+            for mapping in shared {
+                if let Some(i) = mappings.iter().position(|m| *m == mapping) {
+                    config.mappings.push(mappings.remove(i));
+                }
+            }
+            let profile = self.profile.as_deref().unwrap_or(config::DEFAULT_PROFILE);
+            config.profiles.insert(profile.to_string(), mappings);
+        }
+        write_config(path, &config)?;
+        Ok(written)
+    }
+
+    /// Path of the auto-loaded config, e.g. `~/.config/uncpath/mappings.json`
+    /// on Linux. `None` if the platform has no config directory.
+    pub fn default_config_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(CONFIG_DIR).join(CONFIG_FILE))
+    }
+
+    /// Load [`default_config_path`](Self::default_config_path) if it exists.
+    /// A missing file is not an error.
+    pub fn load_default_config(&mut self) -> Result<()> {
+        match Self::default_config_path() {
+            Some(path) if path.is_file() => self.load_from_file(&path),
+            _ => Ok(()),
+        }
+    }
+
+    /// Load mappings from [`ENV_MAPPINGS`]. A missing variable is not an error.
+    pub fn load_from_env(&mut self) -> Result<()> {
+        self.load_from_env_var(ENV_MAPPINGS)
+    }
+
+    /// Load `;`-separated mapping specs from the environment variable
+    /// `name`. A missing variable is not an error.
+    pub fn load_from_env_var(&mut self, name: &str) -> Result<()> {
+        if let Ok(value) = std::env::var(name) {
+            for spec in value.split(';').filter(|s| !s.trim().is_empty()) {
+                self.add(MountMapping::parse_spec(spec)?.with_source(MappingSource::Env))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Add a mapping given as `host/share:/mount/point`.
+    pub fn add_from_cli(&mut self, spec: &str) -> Result<()> {
+        self.add(MountMapping::parse_spec(spec)?.with_source(MappingSource::Cli))
+    }
+
+    /// Map network drive `letter` (either case) to `\\host\share`, so that
+    /// [`parse_input`](crate::convert::parse_input) reads `X:\dir` as
+    /// `\\host\share\dir`. Replaces any earlier mapping for the drive.
+    pub fn add_drive_mapping(&mut self, letter: char, host: &str, share: &str) -> Result<()> {
+        if !letter.is_ascii_alphabetic() {
+            return Err(UncPathError::InvalidMapping(format!(
+                "'{}' is not a drive letter",
+                letter
+            )));
+        }
+        if host.is_empty() || share.is_empty() {
+            return Err(UncPathError::InvalidMapping(format!(
+                "drive {}: needs a host and a share",
+                letter.to_ascii_uppercase()
+            )));
+        }
+        self.drive_mappings.insert(
+            letter.to_ascii_uppercase(),
+            (host.to_string(), share.to_string()),
+        );
+        Ok(())
+    }
+
+    /// Add a drive mapping given as `X=\\server\share` (or `X:=...`).
+    pub fn add_drive_from_cli(&mut self, spec: &str) -> Result<()> {
+        let invalid =
+            || UncPathError::InvalidMapping(format!(r"expected X=\\server\share, got '{}'", spec));
+        let (drive, unc) = spec.trim().split_once('=').ok_or_else(invalid)?;
+        let mut letters = drive.strip_suffix(':').unwrap_or(drive).chars();
+        let (Some(letter), None) = (letters.next(), letters.next()) else {
+            return Err(invalid());
+        };
+        let unc = crate::convert::parse_unc_path(unc).map_err(|_| invalid())?;
+        if !unc.path.trim_matches('/').is_empty() {
+            return Err(UncPathError::InvalidMapping(format!(
+                "drive {}: must map a whole share, not '{}'",
+                letter.to_ascii_uppercase(),
+                unc
+            )));
+        }
+        self.add_drive_mapping(letter, &unc.host, &unc.share)
+    }
+
+    /// The host and share network drive `letter` is mapped to.
+    pub fn drive_mapping(&self, letter: char) -> Option<(&str, &str)> {
+        self.drive_mappings
+            .get(&letter.to_ascii_uppercase())
+            .map(|(host, share)| (host.as_str(), share.as_str()))
+    }
+
+    /// The drive mapped to `host`/`share`, compared like lookups. If
+    /// several are, the first in alphabetical order.
+    pub fn drive_for(&self, host: &str, share: &str) -> Option<char> {
+        let key = self.key(host, share);
+        self.drive_mappings
+            .iter()
+            .filter(|(_, (host, share))| self.key(host, share) == key)
+            .map(|(&letter, _)| letter)
+            .min()
+    }
+
+    /// All drive mappings, keyed by uppercase letter.
+    pub fn drive_mappings(&self) -> &HashMap<char, (String, String)> {
+        &self.drive_mappings
+    }
+
+    pub fn get_mappings(&self) -> &[MountMapping] {
+        &self.mappings
+    }
+
+    /// The mappings in precedence order, lowest first.
+    ///
+    /// ```
+    /// use uncpath::{MappingTable, MountMapping};
+    ///
+    /// let mut table = MappingTable::new();
+    /// assert!(table.is_empty());
+    /// table.add(MountMapping::new("fs01", "team", "/mnt/team"))?;
+    /// table.add(MountMapping::new("fs02", "data", "/mnt/data"))?;
+    /// assert_eq!(table.len(), 2);
+    ///
+    /// let hosts: Vec<&str> = table.iter().map(|m| m.host.as_str()).collect();
+    /// assert_eq!(hosts, ["fs01", "fs02"]);
+    /// for mapping in &table {
+    ///     assert!(mapping.mount_point.starts_with("/mnt/"));
+    /// }
+    /// # Ok::<(), uncpath::UncPathError>(())
+    /// ```
+    pub fn iter(&self) -> std::slice::Iter<'_, MountMapping> {
+        self.mappings.iter()
+    }
+
+    /// Number of mappings, drive mappings not included.
+    pub fn len(&self) -> usize {
+        self.mappings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mappings.is_empty()
+    }
+}
+
+impl<'a> IntoIterator for &'a MappingTable {
+    type Item = &'a MountMapping;
+    type IntoIter = std::slice::Iter<'a, MountMapping>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remove_at_removes_only_that_mapping() {
+        let mut table = MappingTable::try_from_iter(vec![
+            MountMapping::new("fs", "team", "/mnt/old"),
+            MountMapping::new_regex(r"nas-(\w+)", "data", "/mnt/$1"),
+            MountMapping::new("fs", "team", "/mnt/new"),
+        ])
+        .unwrap();
+
+        let removed = table.remove_at(1).unwrap();
+        assert!(removed.regex);
+        assert_eq!(table.find_mount_point("nas-a", "data"), None);
+
+        table.remove_at(1).unwrap();
+        assert_eq!(table.find_mount_point("fs", "team"), Some("/mnt/old"));
+        assert!(table.remove_at(1).is_none());
+    }
+
+    #[test]
+    fn retain_source_keeps_only_mappings_from_that_source() {
+        let file = MappingSource::File(PathBuf::from("/etc/uncpath/mappings.json"));
+        let mut table = MappingTable::try_from_iter(vec![
+            MountMapping::new("fs", "a", "/mnt/a").with_source(MappingSource::Default),
+            MountMapping::new("fs", "b", "/mnt/b").with_source(file.clone()),
+            MountMapping::new("fs", "c", "/mnt/c").with_source(MappingSource::Cli),
+        ])
+        .unwrap();
+
+        assert_eq!(table.retain_source(&file), 2);
+        assert_eq!(table.len(), 1);
+        assert_eq!(table.find_mount_point("fs", "b"), Some("/mnt/b"));
+    }
+
+    #[test]
+    fn load_from_dir_lets_later_files_override_and_load_from_files_reorders() {
+        let dir = std::env::temp_dir().join(format!("uncpath-confd-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let base = r#"{"mappings": [{"host": "fs", "share": "team", "mount_point": "/mnt/base"}]}"#;
+        let local =
+            r#"{"mappings": [{"host": "fs", "share": "team", "mount_point": "/mnt/local"}]}"#;
+        fs::write(dir.join("10-base.json"), base).unwrap();
+        fs::write(dir.join("90-local.json"), local).unwrap();
+        fs::write(dir.join(".hidden.json"), "{").unwrap();
+
+        let mut table = MappingTable::new();
+        table.load_from_dir(&dir).unwrap();
+        assert_eq!(table.find_mount_point("fs", "team"), Some("/mnt/local"));
+
+        let mut files = MappingTable::config_files_in(&dir).unwrap();
+        assert_eq!(files.len(), 2);
+        files.reverse();
+        let mut table = MappingTable::new();
+        table.load_from_files(&files).unwrap();
+        assert_eq!(table.find_mount_point("fs", "team"), Some("/mnt/base"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn try_from_iter_builds_a_table_with_working_lookups() {
+        let table = MappingTable::try_from_iter(vec![
+            MountMapping::new("fs", "team", "/mnt/team"),
+            MountMapping::new("fs", "team/archive", "/mnt/archive"),
+            MountMapping::new_regex(r"nas-(\w+)", "data", "/mnt/$1"),
+        ])
+        .unwrap();
+
+        assert_eq!(table.len(), 3);
+        assert_eq!(table.find_mount_point("FS", "Team"), Some("/mnt/team"));
+        let (m, rest) = table
+            .find_longest_match("fs", "team", "archive/2024")
+            .unwrap();
+        assert_eq!((m.mount_point.as_str(), rest), ("/mnt/archive", "2024"));
+        assert_eq!(
+            table.find_mount_point("nas-team42", "data"),
+            Some("/mnt/$1")
+        );
+    }
+
+    #[test]
+    fn try_from_iter_rejects_invalid_mappings_without_panicking() {
+        let relative = MappingTable::try_from_iter(vec![MountMapping::new("h", "s", "rel")]);
+        assert!(matches!(relative, Err(UncPathError::InvalidMapping(_))));
+
+        let bad_regex = MappingTable::try_from_iter(vec![MountMapping::new_regex("(", "s", "/m")]);
+        assert!(matches!(bad_regex, Err(UncPathError::InvalidRegex(_))));
+
+        let mut table = MappingTable::new();
+        table.set_override_policy(OverridePolicy::Deny);
+        let result = table.try_extend(vec![
+            MountMapping::new("h", "s", "/a"),
+            MountMapping::new("h", "s", "/b"),
+        ]);
+        assert!(result.is_err());
+        assert_eq!(table.find_mount_point("h", "s"), Some("/a"));
+    }
+
+    #[test]
+    fn try_with_defaults_reports_a_malformed_defaults_file() {
+        let path =
+            std::env::temp_dir().join(format!("uncpath-defaults-{}.json", std::process::id()));
+        fs::write(&path, "{").unwrap();
+        let named = MappingTable::defaults_named_by(Some(path.clone().into()));
+        let direct = MappingTable::with_defaults_from(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(named, Err(UncPathError::ConfigError { .. })));
+        assert!(matches!(direct, Err(UncPathError::ConfigError { .. })));
+        for unset in [None, Some(OsString::new())] {
+            let builtin = MappingTable::defaults_named_by(unset).unwrap();
+            assert_eq!(
+                builtin.find_mount_point("server", "shared"),
+                Some("/mnt/shared")
+            );
+        }
+    }
+
+    #[test]
+    fn save_to_file_removes_the_temp_file_when_the_rename_fails() {
+        let dir = std::env::temp_dir().join(format!("uncpath-save-{}", std::process::id()));
+        // A directory in the way makes the final rename fail.
+        let target = dir.join("mappings.json");
+        fs::create_dir_all(target.join("occupied")).unwrap();
+        let table = MappingTable::try_from_iter([MountMapping::new("h", "s", "/m")]).unwrap();
+
+        assert!(table.save_to_file(&target).is_err());
+        assert!(!dir.join("mappings.json.tmp").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn wildcard_host_matches_only_after_exact_mappings() {
+        let table = MappingTable::try_from_iter(vec![
+            MountMapping::new("fileserver*", "shared", "/mnt/fileservers"),
+            MountMapping::new("fileserver01", "shared", "/mnt/fs01"),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            table.find_mount_point("FileServer07", "SHARED"),
+            Some("/mnt/fileservers")
+        );
+        assert_eq!(
+            table.find_mount_point("fileserver01", "shared"),
+            Some("/mnt/fs01")
+        );
+        assert_eq!(table.find_mount_point("otherhost", "shared"), None);
+        assert_eq!(table.find_mount_point("fileserver07", "other"), None);
+    }
+
+    #[test]
+    fn remove_mapping_leaves_the_other_mappings_resolving() {
+        let mut table = MappingTable::try_from_iter(vec![
+            MountMapping::new("fs", "a", "/mnt/a"),
+            MountMapping::new("fs", "b", "/mnt/b"),
+            MountMapping::new("nas", "c", "/mnt/c"),
+        ])
+        .unwrap();
+
+        assert!(table.remove_mapping("FS", "B"));
+        assert!(!table.remove_mapping("fs", "b"));
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.find_mount_point("fs", "b"), None);
+        assert_eq!(table.find_mount_point("fs", "a"), Some("/mnt/a"));
+        assert_eq!(table.find_mount_point("nas", "c"), Some("/mnt/c"));
+
+        table.clear();
+        assert!(table.is_empty());
+        assert_eq!(table.find_mount_point("fs", "a"), None);
+    }
+
+    #[test]
+    fn matching_ignores_case_unless_the_table_is_case_sensitive() {
+        let mut table = MappingTable::try_from_iter(vec![
+            MountMapping::new("Samba", "Data", "/mnt/upper"),
+            MountMapping::new("samba", "data", "/mnt/lower"),
+        ])
+        .unwrap();
+        assert_eq!(table.find_mount_point("SAMBA", "DATA"), Some("/mnt/lower"));
+        assert_eq!(table.find_mount_point("Samba", "Data"), Some("/mnt/lower"));
+
+        table.set_case_sensitive(true);
+        assert_eq!(table.find_mount_point("Samba", "Data"), Some("/mnt/upper"));
+        assert_eq!(table.find_mount_point("samba", "data"), Some("/mnt/lower"));
+        assert_eq!(table.find_mount_point("SAMBA", "DATA"), None);
+
+        table.set_case_sensitive(false);
+        assert_eq!(table.find_mount_point("SAMBA", "DATA"), Some("/mnt/lower"));
+    }
+
+    #[test]
+    fn lookups_ignore_case_while_mappings_keep_theirs_for_display() {
+        let table = MappingTable::try_from_iter(vec![MountMapping::new(
+            "FileServer",
+            "Projects/Q3",
+            "/mnt/Q3",
+        )])
+        .unwrap();
+
+        let (mapping, rest) = table
+            .find_longest_match("fileserver", "PROJECTS", "q3/Report.txt")
+            .unwrap();
+        assert_eq!(rest, "Report.txt");
+        assert_eq!(
+            (
+                mapping.host.as_str(),
+                mapping.share.as_str(),
+                mapping.subpath.as_str()
+            ),
+            ("FileServer", "Projects", "Q3")
+        );
+        assert_eq!(mapping.unc_prefix(), r"\\FileServer\Projects\Q3");
+        assert_eq!(table.get_mappings()[0].host, "FileServer");
+    }
+
+    #[test]
+    fn write_back_keeps_includes_and_other_profiles() {
+        let dir = std::env::temp_dir().join(format!("uncpath-write-back-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("mappings.json");
+        fs::write(
+            dir.join("team.json"),
+            r#"[{"host": "team", "share": "s", "mount_point": "/mnt/team"}]"#,
+        )
+        .unwrap();
+        fs::write(
+            &path,
+            r#"{
+                "include": ["team.json"],
+                "mappings": [{"host": "fs", "share": "shared", "mount_point": "/mnt/shared"}],
+                "profiles": {
+                    "dev": [{"host": "fs", "share": "app", "mount_point": "/mnt/dev"}],
+                    "prod": [{"host": "fs", "share": "app", "mount_point": "/mnt/prod"}]
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let mut table = MappingTable::new();
+        table.set_profile(Some("dev".to_string()));
+        table.load_from_file(&path).unwrap();
+        table
+            .add(
+                MountMapping::new("fs", "new", "/mnt/new")
+                    .with_source(MappingSource::File(path.clone())),
+            )
+            .unwrap();
+        table.add_mapping("cli", "s", "/mnt/cli").unwrap();
+        assert_eq!(table.write_back(&path).unwrap(), 3);
+
+        let config = read_config(&path, false).unwrap();
+        assert_eq!(config.include, [PathBuf::from("team.json")]);
+        assert_eq!(
+            config.mappings,
+            [MountMapping::new("fs", "shared", "/mnt/shared")]
+        );
+        assert_eq!(
+            config.profiles["dev"],
+            [
+                MountMapping::new("fs", "app", "/mnt/dev"),
+                MountMapping::new("fs", "new", "/mnt/new"),
+            ]
+        );
+        assert_eq!(
+            config.profiles["prod"],
+            [MountMapping::new("fs", "app", "/mnt/prod")]
+        );
+
+        // Reloading the saved file gives the same mappings.
+        let mut reloaded = MappingTable::new();
+        reloaded.set_profile(Some("dev".to_string()));
+        reloaded.load_from_file(&path).unwrap();
+        assert_eq!(reloaded.len(), 4);
+        assert_eq!(reloaded.find_mount_point("fs", "new"), Some("/mnt/new"));
+        assert_eq!(reloaded.find_mount_point("team", "s"), Some("/mnt/team"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn mappings_collect_into_a_result_table() {
+        let table: MappingTable = vec![
+            MountMapping::new("fs", "team", "/mnt/team"),
+            MountMapping::new("fs", "team/archive", "/mnt/archive"),
+        ]
+        .into_iter()
+        .collect::<Result<_>>()
+        .unwrap();
+        assert_eq!(table.find_mount_point("fs", "team"), Some("/mnt/team"));
+
+        let rejected: Result<MappingTable> = [
+            MountMapping::new("fs", "team", "/mnt/team"),
+            MountMapping::new_regex("(", "s", "/m"),
+        ]
+        .into_iter()
+        .collect();
+        assert!(matches!(rejected, Err(UncPathError::InvalidRegex(_))));
+    }
+}
@@ -0,0 +1,624 @@
+use anyhow::Result;
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    ExecutableCommand,
+};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+use uncpath::{convert_to_posix, MappingSource, MappingTable, MountMapping, UncPathError};
+
+/// How often the event loop wakes up to look for config changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// A freshly built table and the warnings building it produced.
+pub type Loaded = uncpath::Result<(MappingTable, Vec<String>)>;
+
+/// Config files (or directories of them) to watch, and how to rebuild the
+/// table when one changes.
+pub struct Reload<'a> {
+    pub paths: Vec<PathBuf>,
+    pub load: Box<dyn Fn() -> Loaded + 'a>,
+}
+
+/// Which pane receives key presses.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    Converter,
+    History,
+    Mappings,
+}
+
+/// What the prompt line is collecting a mapping spec for.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum PromptKind {
+    Add,
+    /// Replace the mapping at this position. Regex mappings only have
+    /// their mount point edited, as patterns do not fit the spec syntax.
+    Edit(usize),
+}
+
+/// State of the interactive converter and mapping editor.
+struct App {
+    table: MappingTable,
+    input: String,
+    focus: Focus,
+    mappings: ListState,
+    prompt: Option<(PromptKind, String)>,
+    /// Outcome of the last editor action, shown above the key help.
+    status: Option<String>,
+    /// Successful conversions as `(input, output)`, oldest first.
+    history: Vec<(String, String)>,
+    history_size: usize,
+    history_state: ListState,
+    /// Whether the table has edits that are not saved yet.
+    dirty: bool,
+    /// `r` was pressed once with unsaved edits; a second press reloads.
+    confirm_reload: bool,
+    /// Rebuild the table on the next turn of the event loop.
+    reload_requested: bool,
+}
+
+impl App {
+    fn new(table: MappingTable, warnings: &[String], history_size: usize) -> Self {
+        let mut mappings = ListState::default();
+        if !table.is_empty() {
+            mappings.select(Some(0));
+        }
+        Self {
+            table,
+            input: String::new(),
+            focus: Focus::Converter,
+            mappings,
+            prompt: None,
+            status: warning_status(warnings),
+            history: Vec::new(),
+            history_size,
+            history_state: ListState::default(),
+            dirty: false,
+            confirm_reload: false,
+            reload_requested: false,
+        }
+    }
+
+    /// The conversion of the current input, or `None` while it is empty.
+    fn output(&self) -> Option<uncpath::Result<String>> {
+        let input = self.input.trim();
+        (!input.is_empty()).then(|| convert_to_posix(input, &self.table))
+    }
+
+    fn selected(&self) -> Option<&MountMapping> {
+        self.table.get_mappings().get(self.mappings.selected()?)
+    }
+
+    /// Handle a key press. Returns false when the app should quit.
+    fn on_key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> bool {
+        if self.prompt.is_some() {
+            self.on_prompt_key(code);
+            return true;
+        }
+        self.status = None;
+        let confirming = std::mem::take(&mut self.confirm_reload);
+        if code == KeyCode::Esc {
+            return false;
+        }
+        if code == KeyCode::Tab {
+            self.focus = match self.focus {
+                Focus::Converter => Focus::History,
+                Focus::History => Focus::Mappings,
+                Focus::Mappings => Focus::Converter,
+            };
+            return true;
+        }
+        match self.focus {
+            Focus::Converter => self.on_converter_key(code, modifiers),
+            Focus::History => self.on_history_key(code),
+            Focus::Mappings => self.on_mappings_key(code, confirming),
+        }
+    }
+
+    fn on_converter_key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> bool {
+        match code {
+            // `q` only quits while the input is empty so it can be typed.
+            KeyCode::Char('q') if self.input.is_empty() => return false,
+            KeyCode::Char('u') if modifiers.contains(KeyModifiers::CONTROL) => self.input.clear(),
+            KeyCode::Char(c) => self.input.push(c),
+            KeyCode::Backspace => {
+                self.input.pop();
+            }
+            KeyCode::Enter => self.record(),
+            _ => {}
+        }
+        true
+    }
+
+    /// Add the current conversion to the history if it succeeded, dropping
+    /// the oldest entry beyond `history_size`.
+    fn record(&mut self) {
+        let Some(Ok(output)) = self.output() else {
+            return;
+        };
+        let entry = (self.input.trim().to_string(), output);
+        if self.history.last() != Some(&entry) {
+            self.history.push(entry);
+        }
+        if self.history.len() > self.history_size {
+            let excess = self.history.len() - self.history_size;
+            self.history.drain(..excess);
+        }
+        self.history_state.select(self.history.len().checked_sub(1));
+    }
+
+    fn on_history_key(&mut self, code: KeyCode) -> bool {
+        let len = self.history.len();
+        match code {
+            KeyCode::Char('q') => return false,
+            KeyCode::Up => {
+                let i = self.history_state.selected().unwrap_or(0);
+                self.history_state.select(Some(i.saturating_sub(1)));
+            }
+            KeyCode::Down if len > 0 => {
+                let i = self.history_state.selected().map_or(0, |i| i + 1);
+                self.history_state.select(Some(i.min(len - 1)));
+            }
+            KeyCode::Char('c') => {
+                if let Some((_, output)) = self
+                    .history_state
+                    .selected()
+                    .and_then(|i| self.history.get(i))
+                {
+                    self.status = Some(match copy(output) {
+                        Ok(()) => format!("copied {}", output),
+                        Err(e) => format!("could not copy to clipboard: {}", e),
+                    });
+                }
+            }
+            KeyCode::Enter => {
+                if let Some((input, _)) = self
+                    .history_state
+                    .selected()
+                    .and_then(|i| self.history.get(i))
+                {
+                    self.input = input.clone();
+                    self.focus = Focus::Converter;
+                }
+            }
+            _ => {}
+        }
+        true
+    }
+
+    /// `confirming` is set when the previous key was an `r` that asked
+    /// before dropping unsaved edits.
+    fn on_mappings_key(&mut self, code: KeyCode, confirming: bool) -> bool {
+        let len = self.table.len();
+        match code {
+            KeyCode::Char('q') => return false,
+            KeyCode::Up => {
+                let i = self.mappings.selected().unwrap_or(0);
+                self.mappings.select(Some(i.saturating_sub(1)));
+            }
+            KeyCode::Down if len > 0 => {
+                let i = self.mappings.selected().map_or(0, |i| i + 1);
+                self.mappings.select(Some(i.min(len - 1)));
+            }
+            KeyCode::Char('a') => self.prompt = Some((PromptKind::Add, String::new())),
+            KeyCode::Char('e') | KeyCode::Enter => {
+                if let (Some(i), Some(m)) = (self.mappings.selected(), self.selected()) {
+                    let spec = if m.regex {
+                        m.mount_point.clone()
+                    } else {
+                        spec(m)
+                    };
+                    self.prompt = Some((PromptKind::Edit(i), spec));
+                }
+            }
+            KeyCode::Char('d') => self.delete_selected(),
+            KeyCode::Char('s') => self.save(),
+            KeyCode::Char('r') if self.dirty && !confirming => {
+                self.confirm_reload = true;
+                self.status =
+                    Some("unsaved edits will be lost; press r again to reload".to_string());
+            }
+            KeyCode::Char('r') => self.reload_requested = true,
+            _ => {}
+        }
+        true
+    }
+
+    fn on_prompt_key(&mut self, code: KeyCode) {
+        let Some((kind, spec)) = &mut self.prompt else {
+            return;
+        };
+        match code {
+            KeyCode::Esc => self.prompt = None,
+            KeyCode::Enter => {
+                let (kind, spec) = (*kind, spec.clone());
+                match self.apply(kind, &spec) {
+                    Ok(()) => self.prompt = None,
+                    Err(e) => self.status = Some(e.to_string()),
+                }
+            }
+            KeyCode::Char(c) => spec.push(c),
+            KeyCode::Backspace => {
+                spec.pop();
+            }
+            _ => {}
+        }
+    }
+
+    /// Add a mapping from `spec`, or replace the one being edited. Either
+    /// way the mapping then belongs to the config file, so `s` saves it. An
+    /// edited mapping moves to the end, so it takes precedence. On error
+    /// the table is left as it was.
+    fn apply(&mut self, kind: PromptKind, spec: &str) -> uncpath::Result<()> {
+        let mut table = self.table.clone();
+        let mapping = match kind {
+            PromptKind::Add => MountMapping::parse_spec(spec)?,
+            PromptKind::Edit(i) => {
+                let old = table.remove_at(i).ok_or_else(|| {
+                    UncPathError::InvalidMapping("the edited mapping is gone".to_string())
+                })?;
+                if old.regex {
+                    MountMapping {
+                        mount_point: spec.trim().to_string(),
+                        ..old
+                    }
+                } else {
+                    MountMapping {
+                        aliases: old.aliases,
+                        description: old.description,
+                        tags: old.tags,
+                        ..MountMapping::parse_spec(spec)?
+                    }
+                }
+            }
+        };
+        table.add(mapping.with_source(config_source()))?;
+        self.table = table;
+        self.dirty = true;
+        self.mappings.select(Some(self.table.len() - 1));
+        self.status = Some(match kind {
+            PromptKind::Add => "mapping added".to_string(),
+            PromptKind::Edit(_) => "mapping updated".to_string(),
+        });
+        Ok(())
+    }
+
+    /// Remove the selected mapping, and only that one.
+    fn delete_selected(&mut self) {
+        let Some(mapping) = self
+            .mappings
+            .selected()
+            .and_then(|i| self.table.remove_at(i))
+        else {
+            return;
+        };
+        self.dirty = true;
+        self.status = Some(if *mapping.source() == config_source() {
+            format!("removed {}", mapping.unc_prefix())
+        } else {
+            // Saving cannot delete it from where it came from.
+            format!(
+                "removed {} until restart ({})",
+                mapping.unc_prefix(),
+                mapping.source()
+            )
+        });
+        let len = self.table.len();
+        let selected = self.mappings.selected().unwrap_or(0);
+        self.mappings
+            .select((len > 0).then(|| selected.min(len - 1)));
+    }
+
+    /// Write the mappings that belong to the config file back to it. Those
+    /// from the defaults, the environment, the command line or included
+    /// files stay out, and the file's includes and other profiles are kept.
+    fn save(&mut self) {
+        let Some(path) = MappingTable::default_config_path() else {
+            self.status = Some("no config directory on this platform".to_string());
+            return;
+        };
+        let result = match path.parent() {
+            Some(dir) => fs::create_dir_all(dir).map_err(Into::into),
+            None => Ok(()),
+        }
+        .and_then(|()| self.table.write_back(&path));
+        self.dirty &= result.is_err();
+        self.status = Some(match result {
+            Ok(written) => format!("saved {} mapping(s) to {}", written, path.display()),
+            Err(e) => format!("save failed: {}", e),
+        });
+    }
+
+    /// React to a change of a watched config file: reload right away,
+    /// unless that would throw away unsaved edits.
+    fn config_changed(&mut self) {
+        if self.dirty {
+            self.status =
+                Some("config changed on disk; r reloads it and drops unsaved edits".to_string());
+        } else {
+            self.reload_requested = true;
+        }
+    }
+
+    /// Swap in a freshly loaded table, keeping the old one if loading failed.
+    fn reload(&mut self, loaded: Loaded) {
+        match loaded {
+            Ok((table, warnings)) => {
+                self.table = table;
+                self.dirty = false;
+                // An edit prompt would refer to a position in the old table.
+                self.prompt = None;
+                let len = self.table.len();
+                let selected = self.mappings.selected().unwrap_or(0);
+                self.mappings
+                    .select((len > 0).then(|| selected.min(len - 1)));
+                self.status = Some(
+                    warning_status(&warnings).unwrap_or_else(|| "config reloaded".to_string()),
+                );
+            }
+            Err(e) => self.status = Some(format!("reload failed: {}", e)),
+        }
+    }
+}
+
+/// The status line for `warnings` from building the table: the first
+/// one, and how many more there are.
+fn warning_status(warnings: &[String]) -> Option<String> {
+    let first = warnings.first()?;
+    Some(match warnings.len() - 1 {
+        0 => format!("warning: {}", first),
+        more => format!("warning: {} (+{} more)", first, more),
+    })
+}
+
+fn copy(text: &str) -> std::result::Result<(), arboard::Error> {
+    arboard::Clipboard::new()?.set_text(text)
+}
+
+/// `mapping` as a `host/share[/subpath]:/mount` spec to edit.
+fn spec(mapping: &MountMapping) -> String {
+    let mut spec = format!("{}/{}", mapping.host, mapping.share);
+    if !mapping.subpath.is_empty() {
+        spec.push('/');
+        spec.push_str(&mapping.subpath);
+    }
+    spec.push(':');
+    spec.push_str(&mapping.mount_point);
+    spec
+}
+
+/// Source of mappings added or edited here: the config file `s` saves to.
+fn config_source() -> MappingSource {
+    MappingTable::default_config_path().map_or(MappingSource::Api, MappingSource::File)
+}
+
+/// Run the TUI on `table`, showing `warnings` from building it, keeping at
+/// most `history_size` conversions and reloading the table whenever one of
+/// `reload.paths` changes.
+pub fn run_tui(
+    table: MappingTable,
+    warnings: Vec<String>,
+    history_size: usize,
+    reload: Reload,
+) -> Result<()> {
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    watch(&mut watcher, &reload.paths);
+
+    enable_raw_mode()?;
+    io::stdout().execute(EnterAlternateScreen)?;
+    let backend = CrosstermBackend::new(io::stdout());
+    let mut terminal = Terminal::new(backend)?;
+
+    let mut app = App::new(table, &warnings, history_size);
+    if let Err(e) = run_app(&mut terminal, &mut app, &rx, &reload) {
+        cleanup_terminal(&mut terminal)?; // 出错也要恢复
+        return Err(e);
+    }
+
+    cleanup_terminal(&mut terminal)?;
+    Ok(())
+}
+
+fn cleanup_terminal<B: Backend>(terminal: &mut Terminal<B>) -> Result<()> {
+    disable_raw_mode()?;
+    io::stdout().execute(LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+    Ok(())
+}
+
+/// Watch the directory holding each path rather than the file itself: editors
+/// that save by writing a temporary file and renaming it over the original
+/// replace the inode, which would silently end a watch on the file.
+fn watch(watcher: &mut RecommendedWatcher, paths: &[PathBuf]) {
+    for path in paths {
+        let dir = if path.is_dir() {
+            Some(path.as_path())
+        } else {
+            path.parent().map(|p| {
+                if p.as_os_str().is_empty() {
+                    Path::new(".")
+                } else {
+                    p
+                }
+            })
+        };
+        // A missing config directory just means there is nothing to watch yet.
+        if let Some(dir) = dir {
+            let _ = watcher.watch(dir, RecursiveMode::NonRecursive);
+        }
+    }
+}
+
+/// Whether a filesystem event touches one of the watched config paths.
+fn affects(event: &notify::Event, paths: &[PathBuf]) -> bool {
+    if event.kind.is_access() {
+        return false;
+    }
+    event.paths.iter().any(|changed| {
+        paths.iter().any(|path| {
+            same_file(changed, path)
+                || (path.is_dir() && changed.parent().is_some_and(|p| same_file(p, path)))
+        })
+    })
+}
+
+/// Compare paths as the watcher reports them (absolute) with paths as given.
+fn same_file(a: &Path, b: &Path) -> bool {
+    a == b || std::path::absolute(b).is_ok_and(|b| a == b)
+}
+
+fn run_app<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    rx: &Receiver<notify::Result<notify::Event>>,
+    reload: &Reload,
+) -> Result<()> {
+    loop {
+        terminal.draw(|f| draw(f, app))?;
+
+        if event::poll(POLL_INTERVAL)? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && !app.on_key(key.code, key.modifiers) {
+                    break;
+                }
+            }
+        }
+
+        // An editor save usually arrives as a burst of events; reload once.
+        let changed = rx
+            .try_iter()
+            .filter_map(|event| event.ok())
+            .filter(|event| affects(event, &reload.paths))
+            .count()
+            > 0;
+        if changed {
+            app.config_changed();
+        }
+        if std::mem::take(&mut app.reload_requested) {
+            app.reload((reload.load)());
+        }
+    }
+    Ok(())
+}
+
+/// Border style marking the focused pane.
+fn pane(title: &str, focused: bool) -> Block<'_> {
+    let block = Block::default().title(title).borders(Borders::ALL);
+    if focused {
+        block.border_style(Style::default().fg(Color::Cyan))
+    } else {
+        block
+    }
+}
+
+fn draw(f: &mut Frame, app: &mut App) {
+    let chunks = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Length(3),
+        Constraint::Min(3),
+        Constraint::Length(1),
+        Constraint::Length(1),
+    ])
+    .split(f.size());
+
+    let converting = app.focus == Focus::Converter && app.prompt.is_none();
+    let input = Paragraph::new(app.input.as_str()).block(pane(" UNC path ", converting));
+    f.render_widget(input, chunks[0]);
+    if converting {
+        f.set_cursor(
+            chunks[0].x + 1 + app.input.chars().count() as u16,
+            chunks[0].y + 1,
+        );
+    }
+
+    let output = match app.output() {
+        None => {
+            Paragraph::new("Type or paste a UNC path").style(Style::default().fg(Color::DarkGray))
+        }
+        Some(Ok(path)) => Paragraph::new(path).style(Style::default().fg(Color::Green)),
+        Some(Err(e)) => Paragraph::new(e.to_string()).style(Style::default().fg(Color::Red)),
+    };
+    f.render_widget(output.block(pane(" POSIX path ", false)), chunks[1]);
+
+    let items: Vec<ListItem> = app
+        .table
+        .iter()
+        .map(|m| {
+            ListItem::new(format!(
+                "{} -> {}  ({})",
+                m.unc_prefix(),
+                m.mount_point,
+                m.source()
+            ))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(pane(" Mappings ", app.focus == Focus::Mappings))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let lower = Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(chunks[2]);
+    f.render_stateful_widget(list, lower[1], &mut app.mappings);
+
+    let items: Vec<ListItem> = app
+        .history
+        .iter()
+        .map(|(input, output)| ListItem::new(format!("{} -> {}", input, output)))
+        .collect();
+    let history = List::new(items)
+        .block(pane(" History ", app.focus == Focus::History))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    f.render_stateful_widget(history, lower[0], &mut app.history_state);
+
+    let status = match &app.prompt {
+        Some((kind, spec)) => {
+            let label = match kind {
+                PromptKind::Add => "add host/share[/sub]:/mount> ",
+                PromptKind::Edit(i)
+                    if app.table.get_mappings().get(*i).is_some_and(|m| m.regex) =>
+                {
+                    "mount point> "
+                }
+                PromptKind::Edit(_) => "edit> ",
+            };
+            f.set_cursor(
+                chunks[3].x + (label.chars().count() + spec.chars().count()) as u16,
+                chunks[3].y,
+            );
+            Paragraph::new(format!("{}{}", label, spec))
+        }
+        None => Paragraph::new(app.status.as_deref().unwrap_or_default())
+            .style(Style::default().fg(Color::Yellow)),
+    };
+    f.render_widget(status, chunks[3]);
+
+    if let (Some(_), Some(error)) = (&app.prompt, &app.status) {
+        let error = Paragraph::new(error.as_str()).style(Style::default().fg(Color::Red));
+        f.render_widget(error, chunks[4]);
+        return;
+    }
+    let help = match (app.prompt.is_some(), app.focus) {
+        (true, _) => "Enter confirm · Esc cancel",
+        (false, Focus::Converter) => {
+            "Tab history · Enter keep in history · Esc quit · q quit when empty · Ctrl-U clear"
+        }
+        (false, Focus::History) => "Tab mappings · ↑/↓ select · Enter recall · c copy · q/Esc quit",
+        (false, Focus::Mappings) => {
+            "Tab converter · ↑/↓ select · a add · e edit · d delete · s save · r reload · q/Esc quit"
+        }
+    };
+    f.render_widget(
+        Paragraph::new(help).style(Style::default().fg(Color::DarkGray)),
+        chunks[4],
+    );
+}
@@ -0,0 +1,360 @@
+//! End-to-end tests of the `unc` binary.
+
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+/// Run `unc` with `args` and `stdin`, isolated from the user's config
+/// file and environment.
+fn unc(args: &[&str], stdin: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_unc"))
+        .arg("--no-config")
+        .args(args)
+        .env_remove("UNCPATH_MAPPINGS")
+        .env_remove("UNCPATH_DEFAULTS")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("unc runs");
+    // Feed stdin from another thread so large inputs cannot deadlock
+    // against a full stdout pipe.
+    let mut pipe = child.stdin.take().unwrap();
+    let stdin = stdin.to_vec();
+    let writer = std::thread::spawn(move || pipe.write_all(&stdin));
+    let output = child.wait_with_output().unwrap();
+    writer.join().unwrap().unwrap();
+    output
+}
+
+/// Path of a file under `tests/fixtures`.
+fn fixture(name: &str) -> String {
+    format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+/// A fresh directory for one test's files.
+fn scratch_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("uncpath-cli-{}-{}", test, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn stdin_reports_invalid_utf8_lines_and_continues() {
+    let output = unc(
+        &["--stdin"],
+        b"//server/shared/a\n\xff\xfe\n//server/shared/b\n",
+    );
+    assert_eq!(stdout(&output), "/mnt/shared/a\n/mnt/shared/b\n");
+    assert!(stderr(&output).contains("not valid UTF-8"));
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn null_separated_stdin_reports_invalid_utf8_records_and_continues() {
+    let output = unc(
+        &["--stdin", "-0"],
+        b"//server/shared/a\0\xff\0//server/shared/b\0",
+    );
+    assert_eq!(stdout(&output), "/mnt/shared/a\0/mnt/shared/b\0");
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn toml_mapping_file_is_loaded() {
+    let toml = fixture("mappings.toml");
+    let output = unc(
+        &[
+            "-f",
+            &toml,
+            r"\\fileserver\projects\a.txt",
+            "//NAS/media/film",
+        ],
+        b"",
+    );
+    assert_eq!(stdout(&output), "/mnt/projects/a.txt\n/srv/media/film\n");
+    assert!(output.status.success());
+
+    // Without a known extension the file is tried as JSON, then TOML.
+    let dir = scratch_dir("toml-fallback");
+    let bare = dir.join("mappings");
+    fs::copy(&toml, &bare).unwrap();
+    let output = unc(&["-f", bare.to_str().unwrap(), "//nas/media/x"], b"");
+    assert_eq!(stdout(&output), "/srv/media/x\n");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn to_selects_the_output_representation() {
+    let input = r"\\server\shared\dir\a b.txt";
+    let cases = [
+        ("posix", "/mnt/shared/dir/a b.txt"),
+        ("unc", r"\\server\shared\dir\a b.txt"),
+        ("smb-url", "smb://server/shared/dir/a%20b.txt"),
+        ("file-uri", "file://server/shared/dir/a%20b.txt"),
+        ("nfs-url", "nfs://server/shared/dir/a%20b.txt"),
+    ];
+    for (target, expected) in cases {
+        let output = unc(&["--to", target, input], b"");
+        assert_eq!(
+            stdout(&output),
+            format!("{}\n", expected),
+            "--to {}",
+            target
+        );
+        assert!(output.status.success(), "--to {}", target);
+    }
+}
+
+#[test]
+fn only_the_posix_target_needs_a_mapping() {
+    let input = "//unmapped/share/x";
+    let output = unc(&["--no-defaults", "--to", "smb-url", input], b"");
+    assert_eq!(stdout(&output), "smb://unmapped/share/x\n");
+
+    let output = unc(&["--no-defaults", "--to", "posix", input], b"");
+    assert_eq!(output.status.code(), Some(3));
+}
+
+#[test]
+fn list_shows_mappings_in_their_original_case() {
+    let output = unc(
+        &[
+            "--no-defaults",
+            "-m",
+            "FileServer/Projects:/mnt/Projects",
+            "--list",
+            "--list-format",
+            "plain",
+        ],
+        b"",
+    );
+    assert!(stdout(&output).contains(r"\\FileServer\Projects -> /mnt/Projects"));
+
+    let output = unc(
+        &[
+            "--no-defaults",
+            "-m",
+            "FileServer/Projects:/mnt/Projects",
+            "//FILESERVER/projects/a",
+        ],
+        b"",
+    );
+    assert_eq!(stdout(&output), "/mnt/Projects/a\n");
+}
+
+#[test]
+fn null_mode_keeps_newlines_inside_records() {
+    let output = unc(
+        &["--stdin", "--null"],
+        b"//server/shared/line\nbreak\0//server/shared/b\0",
+    );
+    assert_eq!(stdout(&output), "/mnt/shared/line\nbreak\0/mnt/shared/b\0");
+    assert!(output.status.success());
+}
+
+#[test]
+fn quiet_prints_nothing_and_reports_through_the_exit_code() {
+    for (input, code) in [
+        ("//server/shared/x", 0),
+        ("garbage", 2),
+        ("//nohost/share/x", 3),
+    ] {
+        let output = unc(&["--quiet", input], b"");
+        assert_eq!(output.status.code(), Some(code), "{}", input);
+        assert!(output.stdout.is_empty(), "{}", input);
+        assert!(output.stderr.is_empty(), "{}", input);
+    }
+}
+
+#[test]
+fn each_error_kind_has_its_own_exit_code() {
+    let dir = scratch_dir("exit-codes");
+    let malformed = dir.join("malformed.json");
+    fs::write(&malformed, "{").unwrap();
+    let missing = dir.join("missing.json");
+    let (malformed, missing) = (malformed.to_str().unwrap(), missing.to_str().unwrap());
+
+    let cases: [(&[&str], i32); 7] = [
+        (&["//server/shared/x"], 0),
+        (&["garbage"], 2),
+        (&["//nohost/share/x"], 3),
+        (&["-m", "bad", "//server/shared/x"], 4),
+        (&["-f", malformed, "//server/shared/x"], 5),
+        (&["-f", missing, "//server/shared/x"], 6),
+        (
+            &[
+                "-m",
+                "srv/data:/nonexistent/uncpath",
+                "--strict",
+                "//srv/data/x",
+            ],
+            7,
+        ),
+    ];
+    for (args, code) in cases {
+        let output = unc(args, b"");
+        assert_eq!(output.status.code(), Some(code), "{:?}", args);
+        assert_eq!(stderr(&output).is_empty(), code == 0, "{:?}", args);
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn file_directory_loads_files_in_name_order_with_later_ones_winning() {
+    let dir = scratch_dir("conf-d");
+    fs::write(
+        dir.join("10-base.json"),
+        r#"[{"host": "fs", "share": "team", "mount_point": "/mnt/base"},
+            {"host": "fs", "share": "docs", "mount_point": "/mnt/docs"}]"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.join("90-local.toml"),
+        "[[mapping]]\nhost = \"fs\"\nshare = \"team\"\nmount_point = \"/mnt/local\"\n",
+    )
+    .unwrap();
+    fs::write(dir.join("notes.txt"), "not a config").unwrap();
+
+    let output = unc(
+        &["-f", dir.to_str().unwrap(), "//fs/team/a", "//fs/docs/b"],
+        b"",
+    );
+    assert_eq!(stdout(&output), "/mnt/local/a\n/mnt/docs/b\n");
+    assert!(output.status.success());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn check_config_summarizes_valid_and_invalid_files() {
+    let dir = scratch_dir("check-config");
+    let valid = dir.join("valid.json");
+    fs::write(
+        &valid,
+        r#"[{"host": "fs", "share": "a", "mount_point": "/mnt/a"},
+            {"host": "nas-(\\w+)", "share": "data", "mount_point": "/mnt/$1", "regex": true}]"#,
+    )
+    .unwrap();
+    let relative = dir.join("relative.json");
+    fs::write(
+        &relative,
+        r#"[{"host": "fs", "share": "a", "mount_point": "rel"}]"#,
+    )
+    .unwrap();
+    let conflicting = dir.join("conflicting.json");
+    fs::write(
+        &conflicting,
+        r#"[{"host": "fs", "share": "a", "mount_point": "/mnt/a"},
+            {"host": "FS", "share": "A", "mount_point": "/mnt/b"}]"#,
+    )
+    .unwrap();
+    let [valid, relative, conflicting] =
+        [&valid, &relative, &conflicting].map(|p| p.to_str().unwrap());
+
+    let output = unc(&["--check-config", valid], b"");
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stdout(&output).contains("2 mappings, no problems"));
+
+    for invalid in [relative, conflicting] {
+        let output = unc(&["--check-config", invalid], b"");
+        assert_eq!(output.status.code(), Some(4), "{}", invalid);
+        assert!(stderr(&output).starts_with(invalid), "{}", invalid);
+    }
+
+    let output = unc(&["--check-config", valid, "--check-config", relative], b"");
+    assert_eq!(output.status.code(), Some(4));
+    assert!(stdout(&output).contains("no problems"));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn stdin_streams_many_lines_in_order() {
+    const LINES: usize = 20_000;
+    let mut input = String::new();
+    let mut expected = String::new();
+    for i in 0..LINES {
+        input.push_str(&format!("//server/shared/dir/{}\n", i));
+        expected.push_str(&format!("/mnt/shared/dir/{}\n", i));
+    }
+
+    for jobs in ["1", "4"] {
+        let output = unc(&["--stdin", "--jobs", jobs], input.as_bytes());
+        assert!(output.status.success(), "--jobs {}", jobs);
+        assert_eq!(stdout(&output), expected, "--jobs {}", jobs);
+    }
+
+    // A failure in the middle is reported without stopping the stream.
+    let input = input.replacen("//server/shared/dir/100\n", "//nohost/share/x\n", 1);
+    let output = unc(&["--stdin"], input.as_bytes());
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(stdout(&output).lines().count(), LINES - 1);
+    assert_eq!(stderr(&output).lines().count(), 1);
+}
+
+#[test]
+fn json5_files_load_with_comments_and_trailing_commas() {
+    let json5 = fixture("commented.json5");
+    let output = unc(&["-f", &json5, "//build/artifacts/a", "//nas/media/b"], b"");
+    assert_eq!(stdout(&output), "/mnt/artifacts/a\n/srv/media/b\n");
+    assert!(output.status.success());
+}
+
+#[test]
+fn commented_json_needs_allow_comments() {
+    let json = fixture("commented.json");
+    let output = unc(
+        &["-f", &json, "--allow-comments", "//build/artifacts/a"],
+        b"",
+    );
+    assert_eq!(stdout(&output), "/mnt/artifacts/a\n");
+    assert!(output.status.success());
+
+    // Strict JSON stays the default.
+    let output = unc(&["-f", &json, "//build/artifacts/a"], b"");
+    assert_eq!(output.status.code(), Some(5));
+    assert!(stdout(&output).is_empty());
+    assert!(stderr(&output).contains("commented.json"));
+}
+
+#[test]
+fn verbose_reports_the_mapping_conversion_used() {
+    let output = unc(
+        &[
+            "--no-defaults",
+            "--verbose",
+            "--trim-components",
+            "-m",
+            "fs/share:/mnt/share",
+            "-m",
+            "fs/share/proj:/mnt/proj",
+            "//fs/share/ proj /a",
+        ],
+        b"",
+    );
+    assert_eq!(stdout(&output), "/mnt/proj/a\n");
+    assert!(stderr(&output).contains("path=proj/a"));
+    assert!(stderr(&output).contains(r"matched: \\fs\share\proj -> /mnt/proj"));
+
+    let output = unc(
+        &[
+            "--no-defaults",
+            "--verbose",
+            "--admin-share-root",
+            "/mnt/admin",
+            "//pc/C$/Users",
+        ],
+        b"",
+    );
+    assert_eq!(stdout(&output), "/mnt/admin/pc/c/Users\n");
+    assert!(stderr(&output).contains("matched: administrative share root -> /mnt/admin/pc/c"));
+}